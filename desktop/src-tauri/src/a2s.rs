@@ -0,0 +1,1097 @@
+use std::collections::HashMap;
+use std::fs;
+use std::net::{IpAddr, SocketAddr, UdpSocket};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime};
+use tauri::Manager;
+
+// A2S_RULES request header (followed by a 4-byte challenge number)
+const A2S_RULES_HEADER: [u8; 5] = [0xFF, 0xFF, 0xFF, 0xFF, 0x56];
+
+// A2S_PLAYER request header (followed by a 4-byte challenge number)
+const A2S_PLAYER_HEADER: [u8; 5] = [0xFF, 0xFF, 0xFF, 0xFF, 0x55];
+
+// Player names used by SourceTV / GOTV relays, which occupy a slot but aren't players
+const SOURCETV_PLAYER_NAMES: [&str; 3] = ["sourcetv", "gotv", "cstv"];
+
+// Challenge number placeholder asking the server to issue a new challenge
+pub const CHALLENGE_REQUEST: u32 = 0xFFFF_FFFF;
+
+// How long a challenge number is reused for later queries of the same type to the same server
+const CHALLENGE_CACHE_TTL: Duration = Duration::from_secs(30);
+
+// Rule keys that may carry a direct logo image URL
+const LOGO_RULE_KEYS: [&str; 3] = ["sv_logo", "sv_logo_url", "logo_url"];
+
+// Rule keys that may carry the community website (favicon is used as logo)
+const WEBSITE_RULE_KEYS: [&str; 3] = ["sv_website", "website", "sv_url"];
+
+// Rule keys that may advertise the server tickrate directly
+const TICKRATE_RULE_KEYS: [&str; 2] = ["sv_tickrate", "tickrate"];
+
+// Plausible tickrate range, anything outside is ignored rather than guessed
+const TICKRATE_RANGE: std::ops::RangeInclusive<u32> = 1..=1000;
+
+// Maximum logo image size we're willing to download and cache
+const MAX_LOGO_BYTES: usize = 256 * 1024;
+
+// Redirects followed when fetching a logo (favicons often redirect http -> https)
+const MAX_LOGO_REDIRECTS: usize = 3;
+
+// How long a cached logo is reused before fetching it again
+const LOGO_CACHE_DURATION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+// Image extensions accepted for cached logos
+const LOGO_EXTENSIONS: [&str; 5] = ["png", "jpg", "gif", "webp", "ico"];
+
+// Steam master server region codes with localized display names (code, zh, en)
+// This is the single region table for master server queries and the region filter UI
+pub const MASTER_SERVER_REGIONS: [(u8, &str, &str); 9] = [
+    (0x00, "美国东部", "US East Coast"),
+    (0x01, "美国西部", "US West Coast"),
+    (0x02, "南美洲", "South America"),
+    (0x03, "欧洲", "Europe"),
+    (0x04, "亚洲", "Asia"),
+    (0x05, "澳大利亚", "Australia"),
+    (0x06, "中东", "Middle East"),
+    (0x07, "非洲", "Africa"),
+    (0xFF, "全球", "Rest of World"),
+];
+
+/// Master server region entry returned by list_regions
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct RegionInfo {
+    pub code: u8,
+    pub name_zh: String,
+    pub name_en: String,
+}
+
+/// One entry of an A2S_PLAYER response
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct PlayerInfo {
+    pub name: String,
+    pub score: i32,
+    /// Seconds connected
+    pub duration_secs: f32,
+}
+
+/// Managed state caching the last challenge number per server and request type.
+/// Servers may require a separate challenge for A2S_INFO, A2S_PLAYER and A2S_RULES.
+#[derive(Default)]
+pub struct ChallengeCache(pub Mutex<HashMap<String, (u32, Instant)>>);
+
+/// Reply to a request that may need a challenge
+pub enum ChallengeReply {
+    /// S2C_CHALLENGE ('A'): resend the request with this number
+    Challenge(u32),
+    /// The actual response, this many bytes long
+    Response(usize),
+}
+
+// Cache key: server plus request type byte
+fn challenge_key(ip: &str, port: &str, base_request: &[u8]) -> String {
+    format!("{}:{}:{:02X}", ip, port, base_request.get(4).copied().unwrap_or(0))
+}
+
+// Challenge number still valid for this server and request type, if any
+pub fn cached_challenge(cache: &ChallengeCache, ip: &str, port: &str, base_request: &[u8]) -> Option<u32> {
+    let entries = cache.0.lock().ok()?;
+    let (challenge, received_at) = entries.get(&challenge_key(ip, port, base_request))?;
+    if received_at.elapsed() < CHALLENGE_CACHE_TTL {
+        Some(*challenge)
+    } else {
+        None
+    }
+}
+
+// Remember a challenge number issued for this server and request type
+pub fn store_challenge(cache: &ChallengeCache, ip: &str, port: &str, base_request: &[u8], challenge: u32) {
+    if let Ok(mut entries) = cache.0.lock() {
+        entries.insert(challenge_key(ip, port, base_request), (challenge, Instant::now()));
+    }
+}
+
+/// Where a query socket's packets go. Fresh sockets are connected, so the OS drops replies
+/// from any other address; pooled sockets stay unconnected and use send_to/recv_from, so
+/// replies are checked against the queried address in recv_reply_from.
+#[derive(Clone, Copy, Debug)]
+pub enum Peer {
+    Connected,
+    Unconnected(SocketAddr),
+}
+
+// Send a request with the challenge number appended, returning the exact bytes sent
+pub fn send_with_challenge(socket: &UdpSocket, base_request: &[u8], challenge: u32) -> Result<Vec<u8>, String> {
+    send_with_challenge_to(socket, Peer::Connected, base_request, challenge)
+}
+
+// send_with_challenge for either kind of socket
+pub fn send_with_challenge_to(
+    socket: &UdpSocket,
+    peer: Peer,
+    base_request: &[u8],
+    challenge: u32,
+) -> Result<Vec<u8>, String> {
+    let mut request = base_request.to_vec();
+    request.extend_from_slice(&challenge.to_le_bytes());
+    let sent = match peer {
+        Peer::Connected => socket.send(&request),
+        Peer::Unconnected(addr) => socket.send_to(&request, addr),
+    };
+    sent.map_err(|e| format!("Failed to send query: {}", e))?;
+    Ok(request)
+}
+
+// Receive a reply, telling an S2C_CHALLENGE apart from the actual response
+pub fn recv_reply(socket: &UdpSocket, buf: &mut [u8]) -> Result<ChallengeReply, String> {
+    recv_reply_io(socket, buf).map_err(|e| format!("Failed to receive: {}", e))
+}
+
+// recv_reply keeping the I/O error, for callers that treat a timeout specially
+pub fn recv_reply_io(socket: &UdpSocket, buf: &mut [u8]) -> std::io::Result<ChallengeReply> {
+    let n = socket.recv(buf)?;
+    Ok(classify_reply(buf, n))
+}
+
+// recv_reply_io for either kind of socket. On an unconnected socket, datagrams from any
+// address other than the peer (spoofed or misrouted replies) are logged, counted in
+// mismatches and discarded; waiting continues until the read timeout runs out overall.
+pub fn recv_reply_from(
+    socket: &UdpSocket,
+    peer: Peer,
+    buf: &mut [u8],
+    mismatches: &mut u32,
+) -> std::io::Result<ChallengeReply> {
+    let addr = match peer {
+        Peer::Connected => return recv_reply_io(socket, buf),
+        Peer::Unconnected(addr) => addr,
+    };
+    let deadline = socket.read_timeout()?.map(|timeout| Instant::now() + timeout);
+    loop {
+        let (n, source) = socket.recv_from(buf)?;
+        if source == addr {
+            return Ok(classify_reply(buf, n));
+        }
+        *mismatches += 1;
+        log::warn!("[A2S] Discarded {} byte reply from {} while querying {}", n, source, addr);
+        if let Some(deadline) = deadline {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(std::io::ErrorKind::TimedOut.into());
+            }
+            socket.set_read_timeout(Some(remaining))?;
+        }
+    }
+}
+
+// Tell an S2C_CHALLENGE apart from the actual response
+fn classify_reply(buf: &[u8], n: usize) -> ChallengeReply {
+    if n >= 9 && buf[..4] == [0xFF, 0xFF, 0xFF, 0xFF] && buf[4] == 0x41 {
+        ChallengeReply::Challenge(u32::from_le_bytes([buf[5], buf[6], buf[7], buf[8]]))
+    } else {
+        ChallengeReply::Response(n)
+    }
+}
+
+// Whether a receive error means the read timeout expired (WouldBlock on Unix, TimedOut on Windows)
+pub fn is_timeout(e: &std::io::Error) -> bool {
+    matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut)
+}
+
+// Whether an error means there is no route to the address (network or host unreachable,
+// or no local address of that family), as opposed to the server itself refusing the query.
+// Checked by OS error code since ErrorKind::{Network,Host}Unreachable are newer than the MSRV.
+pub fn is_unreachable(e: &std::io::Error) -> bool {
+    #[cfg(windows)]
+    const UNREACHABLE: [i32; 2] = [10051, 10065]; // WSAENETUNREACH, WSAEHOSTUNREACH
+    #[cfg(target_os = "linux")]
+    const UNREACHABLE: [i32; 2] = [101, 113]; // ENETUNREACH, EHOSTUNREACH
+    #[cfg(target_os = "macos")]
+    const UNREACHABLE: [i32; 2] = [51, 65]; // ENETUNREACH, EHOSTUNREACH
+    #[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+    const UNREACHABLE: [i32; 0] = [];
+
+    e.kind() == std::io::ErrorKind::AddrNotAvailable
+        || e.raw_os_error().is_some_and(|code| UNREACHABLE.contains(&code))
+}
+
+// Ask the server for a challenge number for base_request's type
+// Servers that don't need one for this type answer with the response straight away
+pub fn get_challenge(socket: &UdpSocket, base_request: &[u8], buf: &mut [u8]) -> Result<ChallengeReply, String> {
+    send_with_challenge(socket, base_request, CHALLENGE_REQUEST)?;
+    recv_reply(socket, buf)
+}
+
+// Run a request that may need a challenge, returning the response length in buf
+// A cached challenge is tried first; a new challenge (none cached, or the cached one
+// was rejected) is stored and the request resent once
+pub fn query_with_challenge(
+    socket: &UdpSocket,
+    cache: &ChallengeCache,
+    ip: &str,
+    port: &str,
+    base_request: &[u8],
+    buf: &mut [u8],
+) -> Result<usize, String> {
+    let mut reply = match cached_challenge(cache, ip, port, base_request) {
+        Some(challenge) => {
+            send_with_challenge(socket, base_request, challenge)?;
+            recv_reply(socket, buf)?
+        }
+        None => get_challenge(socket, base_request, buf)?,
+    };
+    if let ChallengeReply::Challenge(challenge) = reply {
+        store_challenge(cache, ip, port, base_request, challenge);
+        send_with_challenge(socket, base_request, challenge)?;
+        reply = recv_reply(socket, buf)?;
+    }
+    match reply {
+        ChallengeReply::Response(n) => Ok(n),
+        ChallengeReply::Challenge(_) => Err("Server rejected the challenge response".to_string()),
+    }
+}
+
+// Largest number of source ports a batch query may rotate across
+pub const MAX_SOURCE_PORTS: usize = 8;
+
+/// A small set of bound UDP sockets (one per source port) that queries rotate through,
+/// so a batch doesn't send everything from one port and trip per-source rate limits.
+/// The sockets are never connected (see Peer::Unconnected) and are closed when the pool is dropped.
+pub struct SocketPool {
+    sockets: Vec<Mutex<UdpSocket>>,
+    source_ip: Option<IpAddr>,
+    ipv6: bool,
+    next: AtomicUsize,
+}
+
+impl SocketPool {
+    /// Bind `count` sockets (clamped to 1..=MAX_SOURCE_PORTS) for querying IPv4 servers.
+    /// recv_buffer_bytes enlarges each socket's receive buffer (SO_RCVBUF) so bursts of
+    /// replies during large scans aren't dropped; None keeps the OS default.
+    pub fn bind(count: usize, source_ip: Option<IpAddr>, recv_buffer_bytes: Option<usize>) -> Result<SocketPool, String> {
+        let count = count.clamp(1, MAX_SOURCE_PORTS);
+        let sockets = (0..count)
+            .map(|_| bind_socket("0.0.0.0", source_ip, Duration::from_secs(5)))
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(bytes) = recv_buffer_bytes {
+            for socket in &sockets {
+                set_recv_buffer(socket, bytes)?;
+            }
+            // The OS may round or cap the request (Linux doubles it, then caps it at rmem_max)
+            if let Some(socket) = sockets.first() {
+                match socket2::SockRef::from(socket).recv_buffer_size() {
+                    Ok(effective) => log::info!("[A2S] Pool receive buffer: requested {} bytes, effective {} bytes", bytes, effective),
+                    Err(e) => log::warn!("[A2S] Failed to read pool receive buffer size: {}", e),
+                }
+            }
+        }
+        let sockets = sockets.into_iter().map(Mutex::new).collect();
+        Ok(SocketPool {
+            sockets,
+            source_ip,
+            ipv6: source_ip.is_some_and(|addr| addr.is_ipv6()),
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    /// Number of source ports in the pool
+    pub fn size(&self) -> usize {
+        self.sockets.len()
+    }
+
+    /// Whether queries to ip from source_ip can use this pool
+    pub fn matches(&self, ip: &str, source_ip: Option<IpAddr>) -> bool {
+        self.source_ip == source_ip && ip.parse::<IpAddr>().is_ok_and(|addr| addr.is_ipv6() == self.ipv6)
+    }
+
+    /// Take the next socket in round-robin order (waits while another query is using it).
+    /// Replies left over from the socket's previous query are discarded.
+    pub fn acquire(&self) -> MutexGuard<'_, UdpSocket> {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.sockets.len();
+        let socket = self.sockets[index].lock().unwrap_or_else(|e| e.into_inner());
+        if socket.set_nonblocking(true).is_ok() {
+            let mut buf = [0u8; 1];
+            while socket.recv(&mut buf).is_ok() {}
+            let _ = socket.set_nonblocking(false);
+        }
+        socket
+    }
+}
+
+// Request a receive buffer (SO_RCVBUF) of this many bytes for a socket
+fn set_recv_buffer(socket: &UdpSocket, bytes: usize) -> Result<(), String> {
+    socket2::SockRef::from(socket)
+        .set_recv_buffer_size(bytes)
+        .map_err(|e| format!("Failed to set receive buffer size: {}", e))
+}
+
+// Create a UDP socket for querying ip, bound to source_ip if set
+pub fn bind_socket(ip: &str, source_ip: Option<IpAddr>, timeout: Duration) -> Result<UdpSocket, String> {
+    let bind_addr = match (source_ip, ip.parse::<IpAddr>()) {
+        (Some(addr), _) => SocketAddr::new(addr, 0),
+        (None, Ok(IpAddr::V6(_))) => SocketAddr::from((std::net::Ipv6Addr::UNSPECIFIED, 0)),
+        (None, _) => SocketAddr::from(([0, 0, 0, 0], 0)),
+    };
+    let socket = UdpSocket::bind(bind_addr).map_err(|e| match source_ip {
+        Some(addr) => format!("Failed to bind to source IP {}: {}", addr, e),
+        None => format!("Failed to create socket: {}", e),
+    })?;
+    socket
+        .set_read_timeout(Some(timeout))
+        .map_err(|e| format!("Failed to set timeout: {}", e))?;
+    Ok(socket)
+}
+
+// Connect a socket to the server
+// IP literals are connected as a SocketAddr so IPv6 targets don't need brackets
+pub fn connect_to(socket: &UdpSocket, ip: &str, port: &str) -> Result<(), String> {
+    connect_to_io(socket, ip, port).map_err(|e| format!("Failed to connect: {}", e))
+}
+
+// connect_to keeping the io::Error, so callers can tell an unreachable address apart
+pub fn connect_to_io(socket: &UdpSocket, ip: &str, port: &str) -> std::io::Result<()> {
+    match (ip.parse::<IpAddr>(), port.parse::<u16>()) {
+        (Ok(addr), Ok(port)) => socket.connect(SocketAddr::new(addr, port)),
+        _ => socket.connect(format!("{}:{}", ip, port)),
+    }
+}
+
+// Open a UDP socket connected to the server, bound to source_ip if set
+pub fn connect_socket(ip: &str, port: &str, source_ip: Option<IpAddr>, timeout: Duration) -> Result<UdpSocket, String> {
+    let socket = bind_socket(ip, source_ip, timeout)?;
+    connect_to(&socket, ip, port)?;
+    Ok(socket)
+}
+
+// Read a null-terminated string, returning None if the terminator is missing
+// (read_cstring then reports a position past the end of the data)
+fn read_terminated(data: &[u8], start: usize) -> Option<(String, usize)> {
+    let (s, next_pos) = crate::read_cstring(data, start);
+    (next_pos <= data.len()).then_some((s, next_pos))
+}
+
+// Header of each packet of a response split across datagrams
+const SPLIT_PACKET_HEADER: [u8; 4] = [0xFE, 0xFF, 0xFF, 0xFF];
+
+// Split packet layout (Source engine): Header(4) + ID(4) + Total(1) + Number(1) + Size(2) + payload
+const SPLIT_PACKET_PAYLOAD_OFFSET: usize = 12;
+
+// Set in the split response ID when the payload is bzip2-compressed
+const SPLIT_PACKET_COMPRESSED: u32 = 0x8000_0000;
+
+// Largest number of packets a split response may claim
+const MAX_SPLIT_PACKETS: u8 = 32;
+
+// One packet of a split response: (ID, total packets, packet number, payload)
+fn parse_split_packet(packet: &[u8]) -> Result<(u32, u8, u8, &[u8]), String> {
+    if packet.len() < SPLIT_PACKET_PAYLOAD_OFFSET || packet[..4] != SPLIT_PACKET_HEADER {
+        return Err("Invalid split packet header".to_string());
+    }
+    let id = u32::from_le_bytes([packet[4], packet[5], packet[6], packet[7]]);
+    if id & SPLIT_PACKET_COMPRESSED != 0 {
+        return Err("Compressed split-packet responses are not supported".to_string());
+    }
+    let (total, number) = (packet[8], packet[9]);
+    if total == 0 || total > MAX_SPLIT_PACKETS || number >= total {
+        return Err(format!("Invalid split packet {} of {}", number, total));
+    }
+    Ok((id, total, number, &packet[SPLIT_PACKET_PAYLOAD_OFFSET..]))
+}
+
+// Receive the rest of a split response whose first-received packet is `first` and return
+// the joined payload (starting with the 0xFFFFFFFF header). Packets may arrive in any order;
+// packets of other responses are skipped. Each wait uses the socket's read timeout.
+fn reassemble_split(socket: &UdpSocket, first: &[u8]) -> Result<Vec<u8>, String> {
+    let (id, total, number, payload) = parse_split_packet(first)?;
+    let mut parts: Vec<Option<Vec<u8>>> = vec![None; total as usize];
+    parts[number as usize] = Some(payload.to_vec());
+
+    let mut buf = vec![0u8; crate::A2S_MAX_PACKET_SIZE];
+    while parts.iter().any(Option::is_none) {
+        let n = socket.recv(&mut buf).map_err(|e| format!("Failed to receive split packet: {}", e))?;
+        match parse_split_packet(&buf[..n]) {
+            Ok((packet_id, packet_total, number, payload)) if packet_id == id && packet_total == total => {
+                parts[number as usize] = Some(payload.to_vec());
+            }
+            Ok(_) => log::warn!("[A2S] Discarded split packet of another response"),
+            Err(e) => log::warn!("[A2S] Discarded packet while reassembling: {}", e),
+        }
+    }
+    Ok(parts.into_iter().flatten().flatten().collect())
+}
+
+// Perform an A2S_RULES query and return the server's rules (cvars) as key/value pairs
+// Servers with many cvars (e.g. running SourceMod) split the response, which is reassembled
+pub fn a2s_rules(
+    ip: &str,
+    port: &str,
+    source_ip: Option<IpAddr>,
+    challenges: &ChallengeCache,
+) -> Result<HashMap<String, String>, String> {
+    let socket = connect_socket(ip, port, source_ip, Duration::from_secs(5))?;
+
+    let mut buf = vec![0u8; crate::A2S_MAX_PACKET_SIZE];
+    let n = query_with_challenge(&socket, challenges, ip, port, &A2S_RULES_HEADER, &mut buf)?;
+    if n >= 4 && buf[..4] == SPLIT_PACKET_HEADER {
+        let data = reassemble_split(&socket, &buf[..n])?;
+        return parse_rules(&data);
+    }
+    parse_rules(&buf[..n])
+}
+
+// Parse an A2S_RULES response (single packet or reassembled)
+fn parse_rules(data: &[u8]) -> Result<HashMap<String, String>, String> {
+    if data.len() < 7 || data[..4] != [0xFF, 0xFF, 0xFF, 0xFF] {
+        return Err("Invalid response header".to_string());
+    }
+    // Verify response type 'E' (0x45) for A2S_RULES response
+    if data[4] != 0x45 {
+        return Err(format!("Invalid response type: 0x{:02X}", data[4]));
+    }
+
+    // Format: Header(4) + Type(1) + Count(2) + (Name + Value) * Count
+    let count = u16::from_le_bytes([data[5], data[6]]);
+    let mut rules = HashMap::new();
+    let mut pos = 7;
+    for _ in 0..count {
+        let (name, next_pos) = match read_terminated(data, pos) {
+            Some(v) => v,
+            None => break,
+        };
+        let (value, next_pos) = match read_terminated(data, next_pos) {
+            Some(v) => v,
+            None => break,
+        };
+        rules.insert(name, value);
+        pos = next_pos;
+    }
+
+    Ok(rules)
+}
+
+// Perform an A2S_PLAYER query and return the player entries
+// Only single-packet responses are supported
+pub fn a2s_players(
+    ip: &str,
+    port: &str,
+    source_ip: Option<IpAddr>,
+    challenges: &ChallengeCache,
+) -> Result<Vec<PlayerInfo>, String> {
+    let socket = connect_socket(ip, port, source_ip, Duration::from_secs(5))?;
+
+    let mut buf = [0u8; 4096];
+    let n = query_with_challenge(&socket, challenges, ip, port, &A2S_PLAYER_HEADER, &mut buf)?;
+
+    if n >= 4 && buf[..4] == [0xFE, 0xFF, 0xFF, 0xFF] {
+        return Err("Split-packet player responses are not supported".to_string());
+    }
+    if n < 6 || buf[..4] != [0xFF, 0xFF, 0xFF, 0xFF] {
+        return Err("Invalid response header".to_string());
+    }
+    // Verify response type 'D' (0x44) for A2S_PLAYER response
+    if buf[4] != 0x44 {
+        return Err(format!("Invalid response type: 0x{:02X}", buf[4]));
+    }
+
+    // Format: Header(4) + Type(1) + Count(1) + (Index(1) + Name + Score(4) + Duration(4)) * Count
+    let data = &buf[..n];
+    let count = data[5];
+    let mut players = Vec::with_capacity(count as usize);
+    let mut pos = 6;
+    for _ in 0..count {
+        let (name, next_pos) = match read_terminated(data, pos + 1) {
+            Some(v) => v,
+            None => break,
+        };
+        let fields = match data.get(next_pos..next_pos + 8) {
+            Some(f) => f,
+            None => break,
+        };
+        players.push(PlayerInfo {
+            name,
+            score: i32::from_le_bytes([fields[0], fields[1], fields[2], fields[3]]),
+            duration_secs: f32::from_le_bytes([fields[4], fields[5], fields[6], fields[7]]),
+        });
+        pos = next_pos + 8;
+    }
+
+    Ok(players)
+}
+
+// Count the entries that look like human players
+// Heuristic: SourceTV/GOTV relays (by name), entries without a name (connecting clients and
+// most plugin bots) and entries with no connection time are not counted.
+// Limits: bots given a real-looking name by a plugin are still counted, and a human who
+// has only just connected may briefly be left out.
+pub fn count_humans(players: &[PlayerInfo]) -> i32 {
+    players
+        .iter()
+        .filter(|p| {
+            let name = p.name.trim();
+            !name.is_empty()
+                && p.duration_secs > 0.0
+                && !SOURCETV_PLAYER_NAMES.iter().any(|tv| name.eq_ignore_ascii_case(tv))
+        })
+        .count() as i32
+}
+
+// Parse a tickrate from a tag like "128tick", "tick128" or "128-tick"
+fn tickrate_from_tag(tag: &str) -> Option<u32> {
+    let tag = tag.trim().to_lowercase();
+    let digits = tag
+        .strip_suffix("tick")
+        .or_else(|| tag.strip_prefix("tick"))?
+        .trim_matches(|c| c == '-' || c == '_' || c == ' ');
+    digits.parse::<u32>().ok().filter(|t| TICKRATE_RANGE.contains(t))
+}
+
+// Detect the tickrate advertised in the server rules (known keys, then sv_tags keywords)
+// Returns None when it can't be determined
+pub fn detect_tickrate(rules: &HashMap<String, String>) -> Option<u32> {
+    let from_keys = TICKRATE_RULE_KEYS
+        .iter()
+        .filter_map(|k| rules.get(*k))
+        .filter_map(|v| v.trim().parse::<f64>().ok())
+        .map(|v| v.round() as u32)
+        .find(|t| TICKRATE_RANGE.contains(t));
+    if from_keys.is_some() {
+        return from_keys;
+    }
+
+    rules
+        .get("sv_tags")?
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .find_map(tickrate_from_tag)
+}
+
+// Pick a logo URL from the server rules: a direct logo URL, or the website's favicon
+fn logo_url_from_rules(rules: &HashMap<String, String>) -> Option<String> {
+    let is_http = |v: &&String| v.starts_with("http://") || v.starts_with("https://");
+
+    if let Some(url) = LOGO_RULE_KEYS.iter().filter_map(|k| rules.get(*k)).find(is_http) {
+        return Some(url.clone());
+    }
+    WEBSITE_RULE_KEYS
+        .iter()
+        .filter_map(|k| rules.get(*k))
+        .find(is_http)
+        .and_then(|site| url::Url::parse(site).ok())
+        .and_then(|site| site.join("/favicon.ico").ok())
+        .map(|u| u.to_string())
+}
+
+// Map an image content type to a cache file extension
+fn logo_extension(content_type: &str) -> Option<&'static str> {
+    match content_type.split(';').next().unwrap_or("").trim() {
+        "image/png" => Some("png"),
+        "image/jpeg" => Some("jpg"),
+        "image/gif" => Some("gif"),
+        "image/webp" => Some("webp"),
+        "image/x-icon" | "image/vnd.microsoft.icon" => Some("ico"),
+        _ => None,
+    }
+}
+
+// Find a cached logo for the server that is still within the cache duration
+fn find_cached_logo(dir: &Path, stem: &str) -> Option<PathBuf> {
+    LOGO_EXTENSIONS
+        .iter()
+        .map(|ext| dir.join(format!("{}.{}", stem, ext)))
+        .find(|path| {
+            fs::metadata(path)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                .is_some_and(|age| age < LOGO_CACHE_DURATION)
+        })
+}
+
+// Resolve the host of a server-supplied logo URL, refusing local and private targets
+// so a server can't point the client at the user's own network
+fn resolve_logo_target(app: &tauri::AppHandle, url: &url::Url) -> Result<(String, SocketAddr), String> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err("Logo URL must use http or https".to_string());
+    }
+    let host = url.host_str().ok_or_else(|| "Logo URL has no host".to_string())?;
+    let port = url.port_or_known_default().unwrap_or(80);
+    let dns = app.state::<crate::dns_cache::DnsCache>();
+    let addrs = crate::dns_cache::resolve_all(&dns, host.trim_start_matches('[').trim_end_matches(']'))?;
+    if addrs.is_empty() || addrs.iter().any(|addr| crate::is_local_address(*addr)) {
+        return Err(format!("Logo host {} is not a public address", host));
+    }
+    Ok((host.to_string(), SocketAddr::new(addrs[0], port)))
+}
+
+// Fetch a logo URL, following up to MAX_LOGO_REDIRECTS redirects. Every hop is checked with
+// resolve_logo_target and connects to the address that was checked.
+async fn fetch_logo_response(
+    app: &tauri::AppHandle,
+    logo_url: &str,
+) -> Result<tauri_plugin_http::reqwest::Response, String> {
+    use tauri_plugin_http::reqwest;
+
+    let mut url = url::Url::parse(logo_url).map_err(|e| format!("Invalid logo URL: {}", e))?;
+    for _ in 0..=MAX_LOGO_REDIRECTS {
+        let app_resolve = app.clone();
+        let target = url.clone();
+        let (host, addr) = tokio::task::spawn_blocking(move || resolve_logo_target(&app_resolve, &target))
+            .await
+            .map_err(|e| format!("Resolve task failed: {}", e))??;
+        let response = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .resolve(&host, addr)
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| format!("Failed to fetch logo: {}", e))?
+            .get(url.as_str())
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch logo: {}", e))?;
+        if response.status().is_redirection() {
+            let location = response
+                .headers()
+                .get("location")
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| "Logo redirect has no location".to_string())?;
+            url = url.join(location).map_err(|e| format!("Invalid logo redirect: {}", e))?;
+            continue;
+        }
+        if !response.status().is_success() {
+            return Err(format!("Failed to fetch logo: HTTP {}", response.status()));
+        }
+        return Ok(response);
+    }
+    Err("Too many logo redirects".to_string())
+}
+
+/// Fetch a server's logo (advertised via A2S_RULES) and cache it in the app cache dir.
+/// Returns the local file path, or an empty string when the server has no known logo source.
+#[tauri::command]
+pub async fn fetch_server_logo(app: tauri::AppHandle, ip: String, port: String) -> Result<String, String> {
+    let cache_dir = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| format!("Failed to get app cache dir: {}", e))?
+        .join("server_logos");
+    let stem: String = format!("{}_{}", ip, port)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect();
+
+    if let Some(path) = find_cached_logo(&cache_dir, &stem) {
+        return Ok(path.to_string_lossy().to_string());
+    }
+
+    let source_ip = crate::parse_source_ip(crate::settings::current(&app).source_ip.as_deref())?;
+    let app_query = app.clone();
+    let rules = tokio::task::spawn_blocking(move || {
+        a2s_rules(&ip, &port, source_ip, &app_query.state::<ChallengeCache>())
+    })
+        .await
+        .map_err(|e| format!("Query task failed: {}", e))?;
+    let logo_url = match rules.ok().as_ref().and_then(logo_url_from_rules) {
+        Some(url) => url,
+        None => return Ok(String::new()),
+    };
+
+    let mut response = fetch_logo_response(&app, &logo_url).await?;
+    if response.content_length().is_some_and(|len| len as usize > MAX_LOGO_BYTES) {
+        return Err("Logo image is too large".to_string());
+    }
+    let extension = response
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .and_then(logo_extension)
+        .ok_or_else(|| "Logo is not a supported image type".to_string())?;
+    // Read chunk by chunk so a body without Content-Length can't grow past the cap
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to read logo: {}", e))?
+    {
+        if bytes.len() + chunk.len() > MAX_LOGO_BYTES {
+            return Err("Logo image is too large".to_string());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    fs::create_dir_all(&cache_dir)
+        .map_err(|e| format!("Failed to create logo cache dir: {}", e))?;
+    let path = cache_dir.join(format!("{}.{}", stem, extension));
+    fs::write(&path, &bytes)
+        .map_err(|e| format!("Failed to cache logo: {}", e))?;
+
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// List the Steam master server regions with localized display names
+#[tauri::command]
+pub async fn list_regions() -> Result<Vec<RegionInfo>, String> {
+    Ok(MASTER_SERVER_REGIONS
+        .iter()
+        .map(|(code, zh, en)| RegionInfo {
+            code: *code,
+            name_zh: zh.to_string(),
+            name_en: en.to_string(),
+        })
+        .collect())
+}
+
+/// Raw A2S exchange result (devtools only)
+#[cfg(feature = "devtools")]
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct RawQueryResult {
+    pub response_base64: String,
+    pub byte_count: usize,
+}
+
+/// Send arbitrary bytes to a server and return whatever comes back, without parsing.
+/// Low-level protocol debugging tool, only available in devtools builds.
+#[cfg(feature = "devtools")]
+#[tauri::command]
+pub async fn raw_a2s_query(
+    app: tauri::AppHandle,
+    ip: String,
+    port: String,
+    payload_base64: String,
+    timeout_ms: u64,
+) -> Result<RawQueryResult, String> {
+    use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine as _};
+
+    let payload = BASE64_STANDARD
+        .decode(payload_base64.trim())
+        .map_err(|e| format!("Base64 decode failed: {}", e))?;
+    let timeout = Duration::from_millis(timeout_ms.clamp(100, 30_000));
+    let source_ip = crate::parse_source_ip(crate::settings::current(&app).source_ip.as_deref())?;
+
+    tokio::task::spawn_blocking(move || {
+        let socket = connect_socket(&ip, &port, source_ip, timeout)?;
+        socket.send(&payload).map_err(|e| format!("Failed to send query: {}", e))?;
+
+        let mut buf = vec![0u8; 65_535];
+        let n = socket.recv(&mut buf).map_err(|e| format!("Failed to receive: {}", e))?;
+        Ok(RawQueryResult {
+            response_base64: BASE64_STANDARD.encode(&buf[..n]),
+            byte_count: n,
+        })
+    })
+    .await
+    .map_err(|e| format!("Query task failed: {}", e))?
+}
+
+/// Outcome of a forced/overridden challenge exchange (devtools only)
+#[cfg(feature = "devtools")]
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ChallengeTestResult {
+    /// Challenge number sent with the A2S_INFO request
+    pub sent_challenge: u32,
+    /// The server didn't issue a challenge, so sent_challenge is an arbitrary number
+    pub synthetic_challenge: bool,
+    /// Server answered with a response instead of issuing a new challenge
+    pub accepted: bool,
+    /// Challenge the server issued instead, when it rejected the sent one
+    pub issued_challenge: Option<u32>,
+    /// Response type byte of the final reply (0x49 = A2S_INFO)
+    pub response_type: Option<u8>,
+    pub byte_count: usize,
+}
+
+/// Send A2S_INFO with a chosen challenge number to test a server's challenge handling.
+/// With no challenge given, a fresh one is requested first and then sent back; servers that
+/// answer without issuing one are sent a random synthetic challenge instead, which forces
+/// the challenge flow on them and shows whether they accept or challenge it.
+/// The challenge cache is bypassed. Only available in devtools builds.
+#[cfg(feature = "devtools")]
+#[tauri::command]
+pub async fn test_a2s_challenge(
+    app: tauri::AppHandle,
+    ip: String,
+    port: String,
+    challenge: Option<u32>,
+) -> Result<ChallengeTestResult, String> {
+    let source_ip = crate::parse_source_ip(crate::settings::current(&app).source_ip.as_deref())?;
+
+    tokio::task::spawn_blocking(move || {
+        let socket = connect_socket(&ip, &port, source_ip, Duration::from_secs(5))?;
+        let mut buf = vec![0u8; crate::A2S_MAX_PACKET_SIZE];
+
+        let (sent_challenge, synthetic_challenge) = match challenge {
+            Some(challenge) => (challenge, false),
+            None => match get_challenge(&socket, &crate::A2S_INFO, &mut buf)? {
+                ChallengeReply::Challenge(issued) => (issued, false),
+                // Anything but the "no challenge" marker, which would just repeat the first request
+                ChallengeReply::Response(_) => {
+                    let synthetic = loop {
+                        let candidate = rand::random::<u32>();
+                        if candidate != CHALLENGE_REQUEST {
+                            break candidate;
+                        }
+                    };
+                    (synthetic, true)
+                }
+            },
+        };
+
+        send_with_challenge(&socket, &crate::A2S_INFO, sent_challenge)?;
+        Ok(match recv_reply(&socket, &mut buf)? {
+            ChallengeReply::Response(n) => ChallengeTestResult {
+                sent_challenge,
+                synthetic_challenge,
+                accepted: true,
+                issued_challenge: None,
+                response_type: buf.get(4).copied().filter(|_| n > 4),
+                byte_count: n,
+            },
+            ChallengeReply::Challenge(issued) => ChallengeTestResult {
+                sent_challenge,
+                synthetic_challenge,
+                accepted: false,
+                issued_challenge: Some(issued),
+                response_type: Some(0x41),
+                byte_count: 9,
+            },
+        })
+    })
+    .await
+    .map_err(|e| format!("Query task failed: {}", e))?
+}
+
+/// One field of an A2S_INFO response as laid out on the wire (devtools only)
+#[cfg(feature = "devtools")]
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct DumpField {
+    pub name: &'static str,
+    pub offset: usize,
+    pub length: usize,
+    pub hex: String,
+    /// Decoded value as text
+    pub value: String,
+}
+
+/// Field-by-field breakdown of an A2S_INFO response (devtools only)
+#[cfg(feature = "devtools")]
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct A2SDebugDump {
+    /// Error of the query itself (the response may still have been dumped)
+    pub query_error: Option<String>,
+    pub byte_count: usize,
+    pub fields: Vec<DumpField>,
+    /// Where the walk stopped early, e.g. a string without its terminator
+    pub parse_error: Option<String>,
+    /// Bytes after the last field the app parses (e.g. the extra data flag block)
+    pub leftover_offset: usize,
+    pub leftover_hex: String,
+}
+
+/// How a fixed-size A2S_INFO field is shown in the dump
+#[cfg(feature = "devtools")]
+enum FieldKind {
+    CString,
+    Byte,
+    Char,
+    U16,
+    U32,
+}
+
+/// Field layout of an A2S_INFO response, in the order parse_a2s_info reads it
+#[cfg(feature = "devtools")]
+const A2S_INFO_LAYOUT: [(&str, FieldKind); 16] = [
+    ("header", FieldKind::U32),
+    ("type", FieldKind::Char),
+    ("protocol", FieldKind::Byte),
+    ("name", FieldKind::CString),
+    ("map", FieldKind::CString),
+    ("folder", FieldKind::CString),
+    ("game", FieldKind::CString),
+    ("app_id", FieldKind::U16),
+    ("players", FieldKind::Byte),
+    ("max_players", FieldKind::Byte),
+    ("bots", FieldKind::Byte),
+    ("server_type", FieldKind::Char),
+    ("environment", FieldKind::Char),
+    ("visibility", FieldKind::Byte),
+    ("vac", FieldKind::Byte),
+    ("version", FieldKind::CString),
+];
+
+/// Walk an A2S_INFO response field by field, recording offsets and raw bytes
+#[cfg(feature = "devtools")]
+fn dump_a2s_info(buf: &[u8]) -> (Vec<DumpField>, Option<String>, usize) {
+    let mut fields = Vec::new();
+    let mut pos = 0;
+    for (name, kind) in A2S_INFO_LAYOUT.iter() {
+        let rest = &buf[pos..];
+        let length = match kind {
+            FieldKind::CString => match rest.iter().position(|&b| b == 0) {
+                Some(end) => end + 1,
+                None => return (fields, Some(format!("{} at offset {} has no NUL terminator", name, pos)), pos),
+            },
+            FieldKind::Byte | FieldKind::Char => 1,
+            FieldKind::U16 => 2,
+            FieldKind::U32 => 4,
+        };
+        if rest.len() < length {
+            return (fields, Some(format!("Response ends inside {} at offset {}", name, pos)), pos);
+        }
+        let bytes = &rest[..length];
+        let value = match kind {
+            FieldKind::CString => String::from_utf8_lossy(&bytes[..length - 1]).to_string(),
+            FieldKind::Byte => bytes[0].to_string(),
+            FieldKind::Char => format!("'{}' (0x{:02X})", bytes[0] as char, bytes[0]),
+            FieldKind::U16 => u16::from_le_bytes([bytes[0], bytes[1]]).to_string(),
+            FieldKind::U32 => format!("0x{:08X}", u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+        };
+        fields.push(DumpField { name, offset: pos, length, hex: hex::encode(bytes), value });
+        pos += length;
+    }
+    (fields, None, pos)
+}
+
+/// Query a server's A2S_INFO and return the response broken down field by field,
+/// with byte offsets, raw hex and anything left unparsed. Builds on the raw capture of
+/// query_server_a2s; only available in devtools builds.
+#[cfg(feature = "devtools")]
+#[tauri::command]
+pub async fn a2s_debug_dump(app: tauri::AppHandle, ip: String, port: String) -> Result<A2SDebugDump, String> {
+    use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine as _};
+
+    let debug = crate::QueryDebug { raw: true, ..Default::default() };
+    let result = crate::query_server(app, ip, port, Some(false), debug, None, None).await?;
+    let response = result
+        .raw
+        .and_then(|raw| raw.response_base64)
+        .ok_or_else(|| result.error.clone().unwrap_or_else(|| "No response received".to_string()))?;
+    let buf = BASE64_STANDARD
+        .decode(response)
+        .map_err(|e| format!("Base64 decode failed: {}", e))?;
+
+    let (fields, parse_error, leftover_offset) = dump_a2s_info(&buf);
+    Ok(A2SDebugDump {
+        query_error: result.error,
+        byte_count: buf.len(),
+        fields,
+        parse_error,
+        leftover_offset,
+        leftover_hex: hex::encode(&buf[leftover_offset..]),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    // Challenge the scripted server issues for each request type byte
+    const SCRIPTED_CHALLENGES: [(u8, u32); 3] = [(0x54, 0x1111_1111), (0x55, 0x2222_2222), (0x56, 0x3333_3333)];
+
+    // Answer `requests` datagrams like a server that wants a separate challenge per request type:
+    // a request carrying that type's challenge gets a response, anything else gets an
+    // S2C_CHALLENGE. Returns the (type, challenge) of every request received.
+    fn scripted_server(server: UdpSocket, requests: usize) -> thread::JoinHandle<Vec<(u8, u32)>> {
+        thread::spawn(move || {
+            let mut seen = Vec::new();
+            let mut buf = [0u8; 1400];
+            for _ in 0..requests {
+                let (n, client) = server.recv_from(&mut buf).unwrap();
+                let kind = buf[4];
+                let challenge = u32::from_le_bytes(buf[n - 4..n].try_into().unwrap());
+                seen.push((kind, challenge));
+                let expected = SCRIPTED_CHALLENGES.iter().find(|(k, _)| *k == kind).unwrap().1;
+                let mut reply = vec![0xFF, 0xFF, 0xFF, 0xFF];
+                if challenge == expected {
+                    // Response type byte: 'I', 'D' or 'E', then a marker so the test can tell them apart
+                    reply.push(match kind {
+                        0x54 => 0x49,
+                        0x55 => 0x44,
+                        _ => 0x45,
+                    });
+                    reply.push(kind);
+                } else {
+                    reply.push(0x41);
+                    reply.extend_from_slice(&expected.to_le_bytes());
+                }
+                server.send_to(&reply, client).unwrap();
+            }
+            seen
+        })
+    }
+
+    #[test]
+    fn challenges_are_kept_per_request_type() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let port = server.local_addr().unwrap().port().to_string();
+        // First round: challenge request + challenged resend per type; second round: cached challenge only
+        let handle = scripted_server(server, 9);
+
+        let socket = connect_socket("127.0.0.1", &port, None, Duration::from_secs(5)).unwrap();
+        let cache = ChallengeCache::default();
+        let requests: [&[u8]; 3] = [&crate::A2S_INFO, &A2S_PLAYER_HEADER, &A2S_RULES_HEADER];
+        for _ in 0..2 {
+            for (request, (kind, _)) in requests.iter().zip(SCRIPTED_CHALLENGES) {
+                let mut buf = [0u8; 1400];
+                let n = query_with_challenge(&socket, &cache, "127.0.0.1", &port, request, &mut buf).unwrap();
+                assert_eq!(n, 6);
+                assert_eq!(buf[5], kind);
+            }
+        }
+
+        for (request, (_, challenge)) in requests.iter().zip(SCRIPTED_CHALLENGES) {
+            assert_eq!(cached_challenge(&cache, "127.0.0.1", &port, request), Some(challenge));
+        }
+        let seen = handle.join().unwrap();
+        let mut expected = Vec::new();
+        for (kind, challenge) in SCRIPTED_CHALLENGES {
+            expected.push((kind, CHALLENGE_REQUEST));
+            expected.push((kind, challenge));
+        }
+        expected.extend(SCRIPTED_CHALLENGES);
+        assert_eq!(seen, expected);
+    }
+
+    // One split packet of response `id`
+    fn split_packet(id: u32, total: u8, number: u8, payload: &[u8]) -> Vec<u8> {
+        let mut packet = SPLIT_PACKET_HEADER.to_vec();
+        packet.extend_from_slice(&id.to_le_bytes());
+        packet.extend_from_slice(&[total, number]);
+        packet.extend_from_slice(&1248u16.to_le_bytes());
+        packet.extend_from_slice(payload);
+        packet
+    }
+
+    #[test]
+    fn split_rules_response_is_reassembled() {
+        let mut payload = vec![0xFF, 0xFF, 0xFF, 0xFF, 0x45];
+        payload.extend_from_slice(&200u16.to_le_bytes());
+        for i in 0..200 {
+            payload.extend_from_slice(format!("sm_cvar_{}\0value_{}\0", i, i).as_bytes());
+        }
+        let chunks: Vec<&[u8]> = payload.chunks(payload.len() / 3 + 1).collect();
+        assert_eq!(chunks.len(), 3);
+
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let client = connect_socket("127.0.0.1", &server.local_addr().unwrap().port().to_string(), None, Duration::from_secs(5)).unwrap();
+        let client_addr = client.local_addr().unwrap();
+        // Out of order, with a packet of another response in between
+        server.send_to(&split_packet(7, 3, 2, chunks[2]), client_addr).unwrap();
+        server.send_to(&split_packet(8, 3, 1, b"stray"), client_addr).unwrap();
+        server.send_to(&split_packet(7, 3, 1, chunks[1]), client_addr).unwrap();
+
+        let data = reassemble_split(&client, &split_packet(7, 3, 0, chunks[0])).unwrap();
+        assert_eq!(data, payload);
+        let rules = parse_rules(&data).unwrap();
+        assert_eq!(rules.len(), 200);
+        assert_eq!(rules["sm_cvar_199"], "value_199");
+    }
+
+    #[test]
+    fn compressed_split_packets_are_rejected() {
+        let packet = split_packet(SPLIT_PACKET_COMPRESSED | 7, 2, 0, b"data");
+        assert!(parse_split_packet(&packet).is_err());
+        assert!(parse_split_packet(&split_packet(7, 2, 2, b"data")).is_err());
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::Manager;
+
+use crate::server_store::{server_key, unix_now};
+use crate::A2SQueryResult;
+
+/// How long a cached A2S result is served before the server is queried again (seconds)
+const A2S_CACHE_TTL_SECS: u64 = 10;
+
+/// Most results kept from the last batch query
+const LAST_RESULTS_MAX: usize = 1000;
+
+/// Persisted entries older than this are dropped on load instead of shown as last-known state (seconds)
+const PERSISTED_MAX_AGE_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// Managed state caching the latest A2S result per server with its Unix timestamp
+#[derive(Default)]
+pub struct A2SCache(pub Mutex<HashMap<String, (A2SQueryResult, u64)>>);
+
+/// Store the latest result for a server
+pub fn store(cache: &A2SCache, result: &A2SQueryResult) {
+    if let Ok(mut entries) = cache.0.lock() {
+        entries.insert(server_key(&result.ip, &result.port), (result.clone(), unix_now()));
+    }
+}
+
+/// Get a cached result if it is still within the TTL
+pub fn get_fresh(cache: &A2SCache, ip: &str, port: &str) -> Option<A2SQueryResult> {
+    let entries = cache.0.lock().ok()?;
+    let (result, cached_at) = entries.get(&server_key(ip, port))?;
+    if unix_now().saturating_sub(*cached_at) < A2S_CACHE_TTL_SECS {
+        Some(result.clone())
+    } else {
+        None
+    }
+}
+
+/// Cached result with its age, as persisted to a2s_cache.json and returned by get_cached_results
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct CachedResult {
+    pub result: A2SQueryResult,
+    /// Unix timestamp of the query
+    pub cached_at: u64,
+    /// Older than the cache TTL: show as "last seen" until a fresh query completes
+    #[serde(default)]
+    pub stale: bool,
+}
+
+/// Get the path of a cache file (a2s_cache.json or last_results.json)
+fn get_cache_path(app: &tauri::AppHandle, file_name: &str) -> Result<PathBuf, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+
+    // Ensure directory exists
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+
+    Ok(app_data_dir.join(file_name))
+}
+
+/// Load the persisted cache from disk, dropping entries older than PERSISTED_MAX_AGE_SECS.
+/// Loaded entries keep their original timestamps, so get_fresh doesn't serve them as current.
+pub fn load(app: &tauri::AppHandle) -> A2SCache {
+    let persisted: Vec<CachedResult> = get_cache_path(app, "a2s_cache.json")
+        .ok()
+        .filter(|path| path.exists())
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| match serde_json::from_str(&json) {
+            Ok(entries) => Some(entries),
+            Err(e) => {
+                log::warn!("[A2SCache] Failed to parse persisted cache: {}", e);
+                None
+            }
+        })
+        .unwrap_or_default();
+
+    let now = unix_now();
+    let entries: HashMap<_, _> = persisted
+        .into_iter()
+        .filter(|entry| now.saturating_sub(entry.cached_at) <= PERSISTED_MAX_AGE_SECS)
+        .map(|entry| (server_key(&entry.result.ip, &entry.result.port), (entry.result, entry.cached_at)))
+        .collect();
+    A2SCache(Mutex::new(entries))
+}
+
+/// Replace the in-memory cache with the persisted one (e.g. after a backup import)
+pub fn reload(app: &tauri::AppHandle) {
+    let loaded = load(app).0.into_inner().unwrap_or_else(|e| e.into_inner());
+    *app.state::<A2SCache>().0.lock().unwrap_or_else(|e| e.into_inner()) = loaded;
+}
+
+/// All cached results with their age, flagged stale once past the TTL
+fn snapshot(cache: &A2SCache) -> Vec<CachedResult> {
+    let entries = cache.0.lock().unwrap_or_else(|e| e.into_inner());
+    let now = unix_now();
+    entries
+        .values()
+        .map(|(result, cached_at)| CachedResult {
+            result: result.clone(),
+            cached_at: *cached_at,
+            stale: now.saturating_sub(*cached_at) >= A2S_CACHE_TTL_SECS,
+        })
+        .collect()
+}
+
+/// Cache size and age, returned by a2s_cache_stats
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct A2SCacheStats {
+    pub entries: usize,
+    /// Age of the oldest entry in seconds (None when empty)
+    pub oldest_age_secs: Option<u64>,
+}
+
+/// Drop cache entries older than max_age_secs, returning how many were removed
+#[tauri::command]
+pub async fn prune_a2s_cache(app: tauri::AppHandle, max_age_secs: u64) -> Result<usize, String> {
+    let cache = app.state::<A2SCache>();
+    let mut entries = cache.0.lock().map_err(|e| e.to_string())?;
+    let now = unix_now();
+    let before = entries.len();
+    entries.retain(|_, (_, cached_at)| now.saturating_sub(*cached_at) <= max_age_secs);
+    Ok(before - entries.len())
+}
+
+/// Get the current A2S cache size
+#[tauri::command]
+pub async fn a2s_cache_stats(app: tauri::AppHandle) -> Result<A2SCacheStats, String> {
+    let cache = app.state::<A2SCache>();
+    let entries = cache.0.lock().map_err(|e| e.to_string())?;
+    let now = unix_now();
+    Ok(A2SCacheStats {
+        entries: entries.len(),
+        oldest_age_secs: entries.values().map(|(_, cached_at)| now.saturating_sub(*cached_at)).max(),
+    })
+}
+
+/// Write the cache to a2s_cache.json so the next launch can show last-known server states.
+/// Returns how many entries were written.
+#[tauri::command]
+pub async fn persist_a2s_cache(app: tauri::AppHandle) -> Result<usize, String> {
+    let entries = snapshot(&app.state::<A2SCache>());
+    let json = serde_json::to_string(&entries)
+        .map_err(|e| format!("Serialization failed: {}", e))?;
+    let path = get_cache_path(&app, "a2s_cache.json")?;
+    fs::write(&path, json).map_err(|e| format!("Failed to save A2S cache: {}", e))?;
+    Ok(entries.len())
+}
+
+/// Get every cached result (including ones restored at startup) for instant list population
+#[tauri::command]
+pub async fn get_cached_results(app: tauri::AppHandle) -> Result<Vec<CachedResult>, String> {
+    Ok(snapshot(&app.state::<A2SCache>()))
+}
+
+/// Results of the most recent server list refresh (query_servers_a2s), persisted to last_results.json for offline viewing
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct LastResults {
+    pub results: Vec<A2SQueryResult>,
+    /// Unix timestamp of the batch query
+    pub captured_at: u64,
+    /// Seconds since captured_at (filled on load)
+    #[serde(default)]
+    pub age_secs: u64,
+    /// Older than the cache TTL: show as "last seen" until a fresh query completes
+    #[serde(default)]
+    pub stale: bool,
+}
+
+/// Persist a batch query's results (the first LAST_RESULTS_MAX of them).
+/// A batch where no server answered (e.g. while offline) keeps the previous results.
+pub fn save_last_results(app: &tauri::AppHandle, results: &[A2SQueryResult]) {
+    if !results.iter().any(|r| r.success) {
+        return;
+    }
+    let last = LastResults {
+        results: results.iter().take(LAST_RESULTS_MAX).cloned().collect(),
+        captured_at: unix_now(),
+        age_secs: 0,
+        stale: false,
+    };
+    let saved = serde_json::to_string(&last)
+        .map_err(|e| format!("Serialization failed: {}", e))
+        .and_then(|json| {
+            let path = get_cache_path(app, "last_results.json")?;
+            fs::write(&path, json).map_err(|e| format!("Failed to save last results: {}", e))
+        });
+    if let Err(e) = saved {
+        log::warn!("[A2SCache] {}", e);
+    }
+}
+
+/// Get the results of the most recent batch query with their capture time and age,
+/// for rendering the server list immediately (e.g. when starting offline). None if never saved.
+#[tauri::command]
+pub async fn load_last_results(app: tauri::AppHandle) -> Result<Option<LastResults>, String> {
+    let path = get_cache_path(&app, "last_results.json")?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let json = fs::read_to_string(&path).map_err(|e| format!("Failed to read last results: {}", e))?;
+    let mut last: LastResults = serde_json::from_str(&json)
+        .map_err(|e| format!("Invalid last results file: {}", e))?;
+    last.age_secs = unix_now().saturating_sub(last.captured_at);
+    last.stale = last.age_secs >= A2S_CACHE_TTL_SECS;
+    Ok(Some(last))
+}
@@ -0,0 +1,270 @@
+use aes_gcm::{
+    aead::{Aead, KeyInit, OsRng},
+    Aes256Gcm, Nonce,
+};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use rand::RngCore;
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Read, Write};
+use tauri::Manager;
+
+use crate::secure_storage::{self, StoredCredentials};
+use crate::{a2s_cache, favorites, power, server_store, settings, url_blocklist};
+
+/// File header identifying a backup archive, followed by the format version byte
+const BACKUP_MAGIC: &[u8; 8] = b"XPROJBAK";
+
+/// Archive layout version: magic + version + salt + nonce + AES-256-GCM(gzip(JSON))
+const BACKUP_VERSION: u8 = 1;
+
+/// Length of the random salt for the passphrase key
+const SALT_LEN: usize = 16;
+
+/// PBKDF2-HMAC-SHA256 rounds for the passphrase key
+const PBKDF2_ROUNDS: u32 = 600_000;
+
+/// Shortest passphrase accepted when exporting
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+/// File extension backup archives must use
+const BACKUP_EXTENSION: &str = "xprojbak";
+
+/// Largest archive import_backup will read
+const MAX_BACKUP_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Decrypted archive contents
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BackupArchive {
+    app_version: String,
+    created_at: u64,
+    /// App data file name -> contents
+    files: BTreeMap<String, String>,
+    /// Decrypted credentials, re-encrypted under the device key on import
+    credentials: Option<StoredCredentials>,
+}
+
+/// What export_backup wrote or import_backup restored
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct BackupManifest {
+    pub path: String,
+    /// App version that created the archive
+    pub app_version: String,
+    pub created_at: u64,
+    pub files: Vec<String>,
+    pub credentials: bool,
+    /// Files in the archive that aren't app data files and were ignored (import only)
+    pub skipped: Vec<String>,
+}
+
+/// App data files included in a backup; credentials are carried decrypted instead of credentials.enc
+fn backup_files() -> impl Iterator<Item = &'static str> {
+    crate::APP_DATA_FILES.into_iter().filter(|name| *name != "credentials.enc")
+}
+
+/// Derive the archive key from the passphrase, so the archive isn't tied to this device
+fn passphrase_key(passphrase: &str, salt: &[u8]) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, PBKDF2_ROUNDS, &mut key);
+    key
+}
+
+fn encrypt_archive(archive: &BackupArchive, passphrase: &str) -> Result<Vec<u8>, String> {
+    let json = serde_json::to_vec(archive).map_err(|e| format!("Serialization failed: {}", e))?;
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(&json)
+        .map_err(|e| format!("Compression failed: {}", e))?;
+    let compressed = encoder.finish().map_err(|e| format!("Compression failed: {}", e))?;
+
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let mut nonce_bytes = [0u8; 12];
+    OsRng.fill_bytes(&mut nonce_bytes);
+    let cipher = Aes256Gcm::new_from_slice(&passphrase_key(passphrase, &salt))
+        .map_err(|e| format!("Failed to create cipher: {}", e))?;
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce_bytes), compressed.as_slice())
+        .map_err(|e| format!("Encryption failed: {}", e))?;
+
+    let mut data = BACKUP_MAGIC.to_vec();
+    data.push(BACKUP_VERSION);
+    data.extend_from_slice(&salt);
+    data.extend_from_slice(&nonce_bytes);
+    data.extend(ciphertext);
+    Ok(data)
+}
+
+fn decrypt_archive(data: &[u8], passphrase: &str) -> Result<BackupArchive, String> {
+    let body = data
+        .strip_prefix(BACKUP_MAGIC.as_slice())
+        .ok_or_else(|| "不是有效的备份文件".to_string())?;
+    let (&version, body) = body.split_first().ok_or_else(|| "不是有效的备份文件".to_string())?;
+    if version != BACKUP_VERSION {
+        return Err(format!("不支持的备份文件版本: {}", version));
+    }
+    if body.len() < SALT_LEN + 12 {
+        return Err("备份文件已损坏".to_string());
+    }
+    let (salt, body) = body.split_at(SALT_LEN);
+    let (nonce, ciphertext) = body.split_at(12);
+
+    let cipher = Aes256Gcm::new_from_slice(&passphrase_key(passphrase, salt))
+        .map_err(|e| format!("Failed to create cipher: {}", e))?;
+    let compressed = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "备份密码错误或文件已损坏".to_string())?;
+
+    let mut json = Vec::new();
+    GzDecoder::new(compressed.as_slice())
+        .read_to_end(&mut json)
+        .map_err(|e| format!("Decompression failed: {}", e))?;
+    serde_json::from_slice(&json).map_err(|e| format!("Invalid backup contents: {}", e))
+}
+
+/// Bundle favorites, settings, credentials, query history and the other app data files
+/// into one archive encrypted under the passphrase (portable to another device).
+/// The archive carries the securecode, so when the securecode gate is enabled the user must
+/// authenticate first (auth_passphrase is the gate's fallback passphrase where there is no OS prompt).
+/// The path must be a .xprojbak file in one of the directories check_user_file_path allows.
+#[tauri::command]
+pub async fn export_backup(
+    app: tauri::AppHandle,
+    path: String,
+    passphrase: String,
+    auth_passphrase: Option<String>,
+) -> Result<BackupManifest, String> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(format!("备份密码至少需要 {} 个字符", MIN_PASSPHRASE_LEN));
+    }
+    let target = crate::check_user_file_path(&app, &path, &[BACKUP_EXTENSION])?;
+
+    let credentials = secure_storage::export_for_backup(&app)?;
+    if credentials.is_some() && settings::current(&app).require_os_auth_for_securecode {
+        let app_auth = app.clone();
+        tokio::task::spawn_blocking(move || crate::os_auth::authenticate(&app_auth, auth_passphrase.as_deref()))
+            .await
+            .map_err(|e| format!("Authentication task failed: {}", e))??;
+    }
+
+    // Write pending query history so the archive has the current records
+    let app_flush = app.clone();
+    let _ = tokio::task::spawn_blocking(move || server_store::flush(&app_flush)).await;
+
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let mut files = BTreeMap::new();
+    for name in backup_files() {
+        let file_path = app_data_dir.join(name);
+        if file_path.exists() {
+            let contents = fs::read_to_string(&file_path)
+                .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+            files.insert(name.to_string(), contents);
+        }
+    }
+    let archive = BackupArchive {
+        app_version: app.package_info().version.to_string(),
+        created_at: server_store::unix_now(),
+        files,
+        credentials,
+    };
+    let manifest = BackupManifest {
+        path: target.to_string_lossy().to_string(),
+        app_version: archive.app_version.clone(),
+        created_at: archive.created_at,
+        files: archive.files.keys().cloned().collect(),
+        credentials: archive.credentials.is_some(),
+        skipped: Vec::new(),
+    };
+
+    // Key derivation is deliberately slow
+    let data = tokio::task::spawn_blocking(move || encrypt_archive(&archive, &passphrase))
+        .await
+        .map_err(|e| format!("Backup task failed: {}", e))??;
+    fs::write(&target, data).map_err(|e| format!("Failed to write backup: {}", e))?;
+
+    log::info!("[Backup] Exported {} files (credentials: {})", manifest.files.len(), manifest.credentials);
+    Ok(manifest)
+}
+
+/// Restore an archive written by export_backup, replacing the current app data.
+/// Credentials are re-encrypted under this device's key. The path is checked like export_backup's.
+/// While the securecode gate is enabled the user must authenticate first (auth_passphrase as in
+/// export_backup), since the restored settings may turn the gate off.
+#[tauri::command]
+pub async fn import_backup(
+    app: tauri::AppHandle,
+    path: String,
+    passphrase: String,
+    auth_passphrase: Option<String>,
+) -> Result<BackupManifest, String> {
+    let source = crate::check_user_file_path(&app, &path, &[BACKUP_EXTENSION])?;
+    let metadata = fs::metadata(&source).map_err(|e| format!("Failed to read backup: {}", e))?;
+    if !metadata.is_file() {
+        return Err("Not a regular file".to_string());
+    }
+    if metadata.len() > MAX_BACKUP_SIZE {
+        return Err("备份文件过大".to_string());
+    }
+    let data = fs::read(&source).map_err(|e| format!("Failed to read backup: {}", e))?;
+    let archive = tokio::task::spawn_blocking(move || decrypt_archive(&data, &passphrase))
+        .await
+        .map_err(|e| format!("Backup task failed: {}", e))??;
+
+    // The archive replaces settings.json and the gate's passphrase file, which could switch the
+    // securecode gate off for the credentials already saved here
+    if settings::current(&app).require_os_auth_for_securecode {
+        let app_auth = app.clone();
+        tokio::task::spawn_blocking(move || crate::os_auth::authenticate(&app_auth, auth_passphrase.as_deref()))
+            .await
+            .map_err(|e| format!("Authentication task failed: {}", e))??;
+    }
+
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+
+    let mut restored = Vec::new();
+    let mut skipped = Vec::new();
+    for (name, contents) in &archive.files {
+        if !backup_files().any(|allowed| allowed == name) {
+            skipped.push(name.clone());
+            continue;
+        }
+        fs::write(app_data_dir.join(name), contents)
+            .map_err(|e| format!("Failed to restore {}: {}", name, e))?;
+        restored.push(name.clone());
+    }
+    let credentials = archive.credentials.is_some();
+    if let Some(stored) = archive.credentials {
+        secure_storage::import_from_backup(&app, stored)?;
+    }
+
+    // Pick up the restored files without a restart
+    settings::reload(&app);
+    server_store::reload(&app);
+    a2s_cache::reload(&app);
+    url_blocklist::reload(&app);
+    favorites::reload_keys(&app);
+    favorites::stop(&app);
+    if let Some(interval_secs) = settings::current(&app).favorites_refresh_interval_secs {
+        favorites::start(&app, interval_secs);
+    }
+    power::refresh(&app).await;
+
+    log::info!("[Backup] Restored {} files (credentials: {})", restored.len(), credentials);
+    Ok(BackupManifest {
+        path: source.to_string_lossy().to_string(),
+        app_version: archive.app_version,
+        created_at: archive.created_at,
+        files: restored,
+        credentials,
+        skipped,
+    })
+}
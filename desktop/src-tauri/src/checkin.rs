@@ -0,0 +1,69 @@
+use tauri::{Emitter, Manager};
+use url::Url;
+
+use crate::forum_bridge;
+
+/// Forum plugin page that performs the daily check-in
+pub const CHECKIN_URL: &str = "https://bbs.upkk.com/plugin.php?id=xnet_core_api:xproj_sign";
+
+/// Outcome read from the check-in page, emitted as checkin-result
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct CheckinResult {
+    pub success: bool,
+    pub message: String,
+    #[serde(default)]
+    pub reward: Option<String>,
+}
+
+/// Reads the Discuz message box shown after the check-in.
+/// alert_right / "成功" means success; "已签到" (already checked in today) counts as success too.
+/// The reward is the "获得 ..." / "奖励 ..." phrase of the message, when present.
+const CHECKIN_PROBE_EXPR: &str = r#"(function() {
+    var el = document.querySelector('#messagetext, .alert_right, .alert_error, .alert_info, .xproj-sign-result');
+    if (!el) return null;
+    var message = (el.innerText || el.textContent || '').replace(/\s+/g, ' ').trim();
+    if (!message) return null;
+    var failed = el.classList.contains('alert_error') || !!el.querySelector('.alert_error');
+    var succeeded = el.classList.contains('alert_right') || !!el.querySelector('.alert_right')
+        || /成功|已签到/.test(message);
+    var reward = message.match(/(?:获得|奖励)[^，。,.!！]*/);
+    return { success: succeeded && !failed, message: message, reward: reward ? reward[0] : null };
+})()"#;
+
+/// Whether a loaded page is the check-in page
+fn is_checkin_page(url: &str) -> bool {
+    let url = match Url::parse(url) {
+        Ok(url) => url,
+        Err(_) => return false,
+    };
+    url.host_str() == Some("bbs.upkk.com")
+        && url.path() == "/plugin.php"
+        && url.query_pairs().any(|(k, v)| k == "id" && v == "xnet_core_api:xproj_sign")
+}
+
+/// After the check-in page loads, read its result and emit checkin-result.
+/// Pages without a recognizable message (e.g. a login prompt) emit nothing.
+pub fn detect_result(window: &tauri::WebviewWindow, url: &str) {
+    if !is_checkin_page(url) {
+        return;
+    }
+    let window = window.clone();
+    tauri::async_runtime::spawn(async move {
+        let app = window.app_handle().clone();
+        let data = match forum_bridge::eval_with_result(&app, &window, CHECKIN_PROBE_EXPR).await {
+            Ok(data) => data,
+            Err(e) => {
+                log::warn!("[Checkin] Failed to read check-in result: {}", e);
+                return;
+            }
+        };
+        match serde_json::from_str::<Option<CheckinResult>>(&data) {
+            Ok(Some(result)) => {
+                log::info!("[Checkin] success={} message={}", result.success, result.message);
+                let _ = app.emit("checkin-result", result);
+            }
+            Ok(None) => log::info!("[Checkin] No check-in message found on page"),
+            Err(e) => log::warn!("[Checkin] Invalid probe result: {}", e),
+        }
+    });
+}
@@ -0,0 +1,54 @@
+use std::time::Duration;
+use tauri::Emitter;
+
+/// Backend whose Date header is used as the reference clock
+const CLOCK_REFERENCE_URL: &str = "https://servers.upkk.com";
+
+/// Skew (seconds) tolerated by timestamp checks before a timestamp is treated as wrong
+pub const CLOCK_SKEW_TOLERANCE_SECS: i64 = 300;
+
+/// Result of check_clock_sanity
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ClockSanity {
+    /// System time minus server time in seconds (positive = local clock is ahead)
+    pub skew_secs: i64,
+    /// Whether the skew is within CLOCK_SKEW_TOLERANCE_SECS
+    pub within_tolerance: bool,
+}
+
+/// Whether a stored Unix timestamp lies further in the future than clock skew can explain
+pub fn is_future_timestamp(timestamp: u64) -> bool {
+    timestamp as i64 > crate::server_store::unix_now() as i64 + CLOCK_SKEW_TOLERANCE_SECS
+}
+
+/// Compare the system clock with the backend's Date header and return the skew.
+/// Emits clock-skew-warning when the skew is large enough to affect timestamp checks.
+#[tauri::command]
+pub async fn check_clock_sanity(app: tauri::AppHandle) -> Result<ClockSanity, String> {
+    let response = tauri_plugin_http::reqwest::Client::new()
+        .head(CLOCK_REFERENCE_URL)
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach time server: {}", e))?;
+    let local_now = crate::server_store::unix_now() as i64;
+
+    let date = response
+        .headers()
+        .get("date")
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| "Server response has no Date header".to_string())?;
+    // HTTP dates ("Tue, 15 Nov 1994 08:12:31 GMT") are valid RFC 2822
+    let server_now = chrono::DateTime::parse_from_rfc2822(date)
+        .map_err(|e| format!("Invalid Date header {:?}: {}", date, e))?
+        .timestamp();
+
+    let skew_secs = local_now - server_now;
+    let within_tolerance = skew_secs.abs() <= CLOCK_SKEW_TOLERANCE_SECS;
+    if !within_tolerance {
+        log::warn!("[Clock] System clock is off by {}s", skew_secs);
+        let _ = app.emit("clock-skew-warning", skew_secs);
+    }
+
+    Ok(ClockSanity { skew_secs, within_tolerance })
+}
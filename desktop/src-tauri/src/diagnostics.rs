@@ -0,0 +1,140 @@
+use std::fs;
+use tauri::Manager;
+
+use crate::{secure_storage, settings};
+
+/// Number of trailing log lines included in a diagnostics bundle
+const LOG_TAIL_LINES: usize = 200;
+
+/// Placeholder written in place of redacted values
+const REDACTED: &str = "<redacted>";
+
+/// Device details that are safe to share (no raw machine ID)
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct DeviceDiagnostics {
+    pub device_id_source: String,
+    pub has_stored_credentials: bool,
+}
+
+/// OS details for a diagnostics bundle
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct OsInfo {
+    pub os: String,
+    pub family: String,
+    pub arch: String,
+}
+
+/// Webview engine rendering the app's windows, returned by get_webview_backend
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct WebviewBackend {
+    /// "WebView2", "WKWebView", "WebKitGTK" or "unknown"
+    pub backend: String,
+    /// Engine version, "unknown" when the platform doesn't report it
+    pub version: String,
+}
+
+/// Detect the platform webview engine and its version
+fn webview_backend() -> WebviewBackend {
+    let backend = if cfg!(windows) {
+        "WebView2"
+    } else if cfg!(target_os = "macos") {
+        "WKWebView"
+    } else if cfg!(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd")) {
+        "WebKitGTK"
+    } else {
+        "unknown"
+    };
+    let version = tauri::webview_version().unwrap_or_else(|e| {
+        log::warn!("[Diagnostics] Failed to read webview version: {}", e);
+        "unknown".to_string()
+    });
+    WebviewBackend { backend: backend.to_string(), version }
+}
+
+/// Diagnostics bundle written by export_diagnostics
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct DiagnosticsBundle {
+    pub app_version: String,
+    pub generated_at: u64,
+    pub os: OsInfo,
+    pub webview: WebviewBackend,
+    pub device: DeviceDiagnostics,
+    pub settings: settings::AppSettings,
+    pub log_tail: Vec<String>,
+}
+
+/// Read the last LOG_TAIL_LINES lines of the most recently modified log file
+fn read_log_tail(app: &tauri::AppHandle) -> Vec<String> {
+    let log_dir = match app.path().app_log_dir() {
+        Ok(dir) => dir,
+        Err(_) => return Vec::new(),
+    };
+    let latest = fs::read_dir(&log_dir)
+        .ok()
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().and_then(|e| e.to_str()) == Some("log"))
+        .max_by_key(|entry| entry.metadata().and_then(|m| m.modified()).ok());
+    let contents = match latest.and_then(|entry| fs::read_to_string(entry.path()).ok()) {
+        Some(c) => c,
+        None => return Vec::new(),
+    };
+    let lines: Vec<&str> = contents.lines().collect();
+    lines[lines.len().saturating_sub(LOG_TAIL_LINES)..]
+        .iter()
+        .map(|line| line.to_string())
+        .collect()
+}
+
+/// Collect a diagnostics bundle with credentials and account IDs redacted
+fn collect(app: &tauri::AppHandle) -> DiagnosticsBundle {
+    let mut app_settings = settings::current(app);
+    if app_settings.last_active_steamid64.is_some() {
+        app_settings.last_active_steamid64 = Some(REDACTED.to_string());
+    }
+
+    let has_stored_credentials = app
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join("credentials.enc").exists())
+        .unwrap_or(false);
+
+    DiagnosticsBundle {
+        app_version: app.package_info().version.to_string(),
+        generated_at: crate::server_store::unix_now(),
+        os: OsInfo {
+            os: std::env::consts::OS.to_string(),
+            family: std::env::consts::FAMILY.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+        },
+        webview: webview_backend(),
+        device: DeviceDiagnostics {
+            device_id_source: secure_storage::device_id_source(app).to_string(),
+            has_stored_credentials,
+        },
+        settings: app_settings,
+        log_tail: read_log_tail(app),
+    }
+}
+
+/// Write a diagnostics bundle (JSON) for bug reports and return the path written.
+/// Like write_text_file, only .json paths inside the allowed directories are accepted.
+#[tauri::command]
+pub async fn export_diagnostics(app: tauri::AppHandle, path: String) -> Result<String, String> {
+    let p = crate::check_user_file_path(&app, &path, &["json"])?;
+
+    let bundle = collect(&app);
+    let json = serde_json::to_string_pretty(&bundle)
+        .map_err(|e| format!("Serialization failed: {}", e))?;
+    fs::write(&p, json).map_err(|e| format!("Failed to write diagnostics: {}", e))?;
+
+    log::info!("[Diagnostics] Exported diagnostics to {}", p.display());
+    Ok(p.to_string_lossy().to_string())
+}
+
+/// Webview engine and version the app is running on, for triaging backend-specific rendering bugs
+#[tauri::command]
+pub async fn get_webview_backend() -> Result<WebviewBackend, String> {
+    Ok(webview_backend())
+}
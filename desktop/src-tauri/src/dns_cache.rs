@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, ToSocketAddrs};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::Manager;
+
+/// How long a resolved address is reused before the hostname is looked up again
+const DNS_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Managed state caching hostname -> IP lookups
+/// (at most one address per family, preferred family first)
+#[derive(Default)]
+pub struct DnsCache(pub Mutex<HashMap<String, (Vec<IpAddr>, Instant)>>);
+
+/// Resolve a hostname to its candidate addresses, reusing a cached result while it is fresh.
+/// Returns at most one address per family, IPv4 first since A2S sockets bind to an IPv4
+/// address by default. IP literals are returned as-is without touching the cache.
+pub fn resolve_all(cache: &DnsCache, host: &str) -> Result<Vec<IpAddr>, String> {
+    let host = host.trim();
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return Ok(vec![ip]);
+    }
+
+    if let Ok(entries) = cache.0.lock() {
+        if let Some((addrs, resolved_at)) = entries.get(host) {
+            if resolved_at.elapsed() < DNS_CACHE_TTL {
+                return Ok(addrs.clone());
+            }
+        }
+    }
+
+    let resolved: Vec<IpAddr> = (host, 0)
+        .to_socket_addrs()
+        .map_err(|e| format!("Failed to resolve {}: {}", host, e))?
+        .map(|a| a.ip())
+        .collect();
+    let addrs: Vec<IpAddr> = [
+        resolved.iter().find(|a| a.is_ipv4()),
+        resolved.iter().find(|a| a.is_ipv6()),
+    ]
+    .into_iter()
+    .flatten()
+    .copied()
+    .collect();
+    if addrs.is_empty() {
+        return Err(format!("No addresses found for {}", host));
+    }
+
+    if let Ok(mut entries) = cache.0.lock() {
+        entries.insert(host.to_string(), (addrs.clone(), Instant::now()));
+    }
+    Ok(addrs)
+}
+
+/// Resolve a hostname to a single IP address (the preferred family)
+pub fn resolve(cache: &DnsCache, host: &str) -> Result<IpAddr, String> {
+    resolve_all(cache, host)?
+        .first()
+        .copied()
+        .ok_or_else(|| format!("No addresses found for {}", host.trim()))
+}
+
+/// Resolve a batch of hostnames once, returning host -> IP for every host that resolved.
+/// Results are cached so repeated refreshes don't repeat the DNS lookups.
+#[tauri::command]
+pub async fn resolve_hosts(app: tauri::AppHandle, hosts: Vec<String>) -> Result<HashMap<String, String>, String> {
+    tokio::task::spawn_blocking(move || {
+        let cache = app.state::<DnsCache>();
+        let mut resolved = HashMap::new();
+        for host in hosts {
+            match resolve(&cache, &host) {
+                Ok(ip) => {
+                    resolved.insert(host, ip.to_string());
+                }
+                Err(e) => log::warn!("[DNS] {}", e),
+            }
+        }
+        resolved
+    })
+    .await
+    .map_err(|e| format!("Resolve task failed: {}", e))
+}
+
+/// Longest a reverse lookup may take before it is given up on
+const REVERSE_DNS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// PTR hostname of an address (None when there is no record, or the lookup fails or times out).
+/// The lookup runs on the blocking pool; a slow resolver is abandoned rather than awaited.
+pub async fn reverse_lookup(ip: IpAddr) -> Option<String> {
+    let lookup = tokio::task::spawn_blocking(move || dns_lookup::lookup_addr(&ip));
+    match tokio::time::timeout(REVERSE_DNS_TIMEOUT, lookup).await {
+        Ok(Ok(Ok(name))) => Some(name.trim_end_matches('.').to_string()),
+        Ok(Ok(Err(_))) => None,
+        Ok(Err(e)) => {
+            log::warn!("[DNS] Reverse lookup task failed: {}", e);
+            None
+        }
+        Err(_) => {
+            log::info!("[DNS] Reverse lookup of {} timed out", ip);
+            None
+        }
+    }
+}
+
+/// Reverse DNS (PTR) hostname of an IP address, None if it has none
+#[tauri::command]
+pub async fn reverse_dns(ip: String) -> Result<Option<String>, String> {
+    let ip: IpAddr = ip.trim().parse().map_err(|_| format!("Invalid IP address: {}", ip))?;
+    Ok(reverse_lookup(ip).await)
+}
@@ -0,0 +1,251 @@
+use rand::Rng;
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+use crate::server_store::server_key;
+use crate::settings;
+
+/// Shortest allowed autorefresh interval, keeps the app from hammering servers
+const MIN_REFRESH_INTERVAL_SECS: u64 = 15;
+
+/// Upper bound on the random per-server delay within one refresh round
+const MAX_REFRESH_JITTER: Duration = Duration::from_secs(10);
+
+/// Maximum favorites queried at once during a refresh round
+const REFRESH_CONCURRENCY: usize = 8;
+
+/// Largest server list file import_servers_txt will read
+const MAX_IMPORT_FILE_SIZE: u64 = 1024 * 1024;
+
+/// A favorited server
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct Favorite {
+    pub ip: String,
+    pub port: String,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// Servers parsed from an imported list plus the lines that couldn't be used
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ImportedServers {
+    pub favorites: Vec<Favorite>,
+    pub rejected: Vec<crate::RejectedEntry>,
+}
+
+/// Managed state holding the running autorefresh task
+#[derive(Default)]
+pub struct FavoritesRefresh(Mutex<Option<tauri::async_runtime::JoinHandle<()>>>);
+
+/// Managed state holding the server keys of the saved favorites, so queries can flag
+/// favorites without reading favorites.json
+#[derive(Default)]
+pub struct FavoriteKeys(Mutex<HashSet<String>>);
+
+fn keys_of(favorites: &[Favorite]) -> HashSet<String> {
+    favorites.iter().map(|favorite| server_key(&favorite.ip, &favorite.port)).collect()
+}
+
+/// Get favorites file path
+fn get_favorites_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+
+    // Ensure directory exists
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+
+    Ok(app_data_dir.join("favorites.json"))
+}
+
+/// Load favorites from disk (empty if missing or unreadable)
+pub fn load_favorites(app: &tauri::AppHandle) -> Vec<Favorite> {
+    get_favorites_path(app)
+        .ok()
+        .filter(|path| path.exists())
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| match serde_json::from_str(&json) {
+            Ok(favorites) => Some(favorites),
+            Err(e) => {
+                log::warn!("[Favorites] Failed to parse favorites: {}", e);
+                None
+            }
+        })
+        .unwrap_or_default()
+}
+
+/// Load the favorite keys at startup
+pub fn load_keys(app: &tauri::AppHandle) -> FavoriteKeys {
+    FavoriteKeys(Mutex::new(keys_of(&load_favorites(app))))
+}
+
+/// Re-read favorites.json into the key set (e.g. after a backup import or data reset)
+pub fn reload_keys(app: &tauri::AppHandle) {
+    let keys = keys_of(&load_favorites(app));
+    *app.state::<FavoriteKeys>().0.lock().unwrap_or_else(|e| e.into_inner()) = keys;
+}
+
+/// Whether ip:port is one of the saved favorites
+pub fn is_favorite(app: &tauri::AppHandle, ip: &str, port: &str) -> bool {
+    let state = app.state::<FavoriteKeys>();
+    let keys = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    keys.contains(&server_key(ip, port))
+}
+
+/// Write favorites to disk and update the key set
+fn save_favorites(app: &tauri::AppHandle, favorites: &[Favorite]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(favorites)
+        .map_err(|e| format!("Serialization failed: {}", e))?;
+    let path = get_favorites_path(app)?;
+    fs::write(&path, json).map_err(|e| format!("Failed to save favorites: {}", e))?;
+    *app.state::<FavoriteKeys>().0.lock().unwrap_or_else(|e| e.into_inner()) = keys_of(favorites);
+    Ok(())
+}
+
+/// Query every favorite once, spreading the queries over a random per-server delay
+/// so servers aren't hit in lockstep
+async fn refresh_round(app: &tauri::AppHandle, interval: Duration) {
+    let favorites = load_favorites(app);
+    if favorites.is_empty() {
+        return;
+    }
+
+    let max_jitter_ms = (interval / 4).min(MAX_REFRESH_JITTER).as_millis() as u64;
+    let semaphore = Arc::new(tokio::sync::Semaphore::new(REFRESH_CONCURRENCY));
+    let tasks: Vec<_> = favorites
+        .into_iter()
+        .map(|favorite| {
+            let app = app.clone();
+            let semaphore = semaphore.clone();
+            let jitter = Duration::from_millis(rand::thread_rng().gen_range(0..=max_jitter_ms));
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(jitter).await;
+                let _permit = semaphore.acquire_owned().await.ok()?;
+                crate::query_server_a2s(app, favorite.ip, favorite.port, Some(true), None, None, None).await.ok()
+            })
+        })
+        .collect();
+
+    let mut results = Vec::new();
+    for task in tasks {
+        if let Ok(Some(result)) = task.await {
+            results.push(result);
+        }
+    }
+    // Paused mid-round: the rest of the round was skipped, so don't report it as a refresh
+    if crate::network_paused(app) {
+        return;
+    }
+    crate::notifications::observe(app, &results);
+    let _ = app.emit("favorites-refreshed", &results);
+}
+
+/// Start the autorefresh loop, replacing any loop already running
+pub fn start(app: &tauri::AppHandle, interval_secs: u64) {
+    let interval = Duration::from_secs(interval_secs);
+    let app_loop = app.clone();
+    let handle = tauri::async_runtime::spawn(async move {
+        loop {
+            let wait = if crate::power::is_enabled(&app_loop) {
+                interval * crate::power::POWER_SAVER_REFRESH_FACTOR
+            } else {
+                interval
+            };
+            tokio::time::sleep(wait).await;
+            if crate::network_paused(&app_loop) {
+                continue;
+            }
+            refresh_round(&app_loop, interval).await;
+        }
+    });
+
+    let state = app.state::<FavoritesRefresh>();
+    let previous = state.0.lock().unwrap_or_else(|e| e.into_inner()).replace(handle);
+    if let Some(previous) = previous {
+        previous.abort();
+    }
+    log::info!("[Favorites] Autorefresh every {}s", interval_secs);
+    if settings::current(app).notifications.enabled {
+        crate::notifications::check_permission(app);
+    }
+}
+
+/// Stop the autorefresh loop if it is running
+pub fn stop(app: &tauri::AppHandle) {
+    let state = app.state::<FavoritesRefresh>();
+    let running = state.0.lock().unwrap_or_else(|e| e.into_inner()).take();
+    if let Some(handle) = running {
+        handle.abort();
+        log::info!("[Favorites] Autorefresh stopped");
+    }
+}
+
+/// Get the saved favorites
+#[tauri::command]
+pub async fn get_favorites(app: tauri::AppHandle) -> Result<Vec<Favorite>, String> {
+    Ok(load_favorites(&app))
+}
+
+/// Replace the saved favorites
+#[tauri::command]
+pub async fn set_favorites(app: tauri::AppHandle, favorites: Vec<Favorite>) -> Result<(), String> {
+    save_favorites(&app, &favorites)
+}
+
+/// Periodically refresh favorites in the background, emitting favorites-refreshed with the results.
+/// Skipped while the network is paused; the interval is persisted and resumed on next launch.
+#[tauri::command]
+pub async fn start_favorites_autorefresh(app: tauri::AppHandle, interval_secs: u64) -> Result<(), String> {
+    if interval_secs < MIN_REFRESH_INTERVAL_SECS {
+        return Err(format!("interval_secs must be at least {}", MIN_REFRESH_INTERVAL_SECS));
+    }
+    settings::update(&app, |s| s.favorites_refresh_interval_secs = Some(interval_secs))?;
+    start(&app, interval_secs);
+    Ok(())
+}
+
+/// Stop refreshing favorites in the background
+#[tauri::command]
+pub async fn stop_favorites_autorefresh(app: tauri::AppHandle) -> Result<(), String> {
+    settings::update(&app, |s| s.favorites_refresh_interval_secs = None)?;
+    stop(&app);
+    Ok(())
+}
+
+/// Import servers from a .txt file with one ip:port per line.
+/// Blank lines and # comments are skipped; lines go through the same normalization as batch queries.
+/// The file must be in one of the directories check_user_file_path allows.
+#[tauri::command]
+pub async fn import_servers_txt(app: tauri::AppHandle, path: String) -> Result<ImportedServers, String> {
+    let p = crate::check_user_file_path(&app, &path, &["txt"])?;
+    let metadata = fs::metadata(&p).map_err(|e| format!("Failed to read file: {}", e))?;
+    if !metadata.is_file() {
+        return Err("Not a regular file".to_string());
+    }
+    if metadata.len() > MAX_IMPORT_FILE_SIZE {
+        return Err(format!("File is larger than {} bytes", MAX_IMPORT_FILE_SIZE));
+    }
+
+    let contents = fs::read_to_string(&p).map_err(|e| format!("Failed to read file: {}", e))?;
+    let lines: Vec<String> = contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    let list = crate::normalize_entries(lines);
+    Ok(ImportedServers {
+        favorites: list
+            .servers
+            .into_iter()
+            .map(|server| Favorite { ip: server.ip, port: server.port, name: None })
+            .collect(),
+        rejected: list.rejected,
+    })
+}
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+use tokio::sync::oneshot;
+use url::Url;
+
+/// Scheme/host used by injected scripts to hand results back to Rust.
+/// Like xproj://auth/callback, these navigations are intercepted and cancelled.
+const BRIDGE_URL_PREFIX: &str = "xproj://forum/";
+
+/// How long to wait for an injected script to report back
+const BRIDGE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Managed state tracking scripts waiting for a result from a webview
+/// and the URLs reported by in-page (SPA) navigation
+#[derive(Default)]
+pub struct ForumBridge {
+    next_id: AtomicU64,
+    pending: Mutex<HashMap<u64, oneshot::Sender<String>>>,
+    spa_urls: Mutex<HashMap<String, String>>,
+}
+
+/// URL change reported by the SPA hook, emitted as forum-url-changed
+#[derive(serde::Serialize, Clone, Debug)]
+struct UrlChanged {
+    label: String,
+    url: String,
+}
+
+/// Wrap a JS expression so its JSON-serialized value is sent back through the bridge
+fn result_script(id: u64, expression: &str) -> String {
+    format!(
+        r#"(function() {{
+    var data;
+    try {{
+        data = JSON.stringify((function() {{ return {expression}; }})());
+    }} catch (e) {{
+        console.error('[XProj] Bridge script failed:', e);
+        data = 'null';
+    }}
+    window.location.href = '{prefix}result?id={id}&data=' + encodeURIComponent(data === undefined ? 'null' : data);
+}})();"#,
+        expression = expression,
+        prefix = BRIDGE_URL_PREFIX,
+        id = id,
+    )
+}
+
+/// Evaluate a JS expression in a window and wait for its JSON-encoded value
+pub async fn eval_with_result(
+    app: &tauri::AppHandle,
+    window: &tauri::WebviewWindow,
+    expression: &str,
+) -> Result<String, String> {
+    let bridge = app.state::<ForumBridge>();
+    let id = bridge.next_id.fetch_add(1, Ordering::SeqCst);
+    let (tx, rx) = oneshot::channel();
+    bridge.pending.lock().map_err(|e| e.to_string())?.insert(id, tx);
+
+    if let Err(e) = window.eval(result_script(id, expression)) {
+        bridge.pending.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+        return Err(e.to_string());
+    }
+
+    match tokio::time::timeout(BRIDGE_TIMEOUT, rx).await {
+        Ok(Ok(data)) => Ok(data),
+        _ => {
+            bridge.pending.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+            Err("页面脚本无响应".to_string())
+        }
+    }
+}
+
+/// Latest URL reported by client-side navigation in a window, if any since the last page load
+pub fn spa_url(app: &tauri::AppHandle, label: &str) -> Option<String> {
+    let bridge = app.state::<ForumBridge>();
+    let urls = bridge.spa_urls.lock().unwrap_or_else(|e| e.into_inner());
+    urls.get(label).cloned()
+}
+
+/// Forget the SPA-reported URL after a full page load (the webview URL is current again)
+pub fn clear_spa_url(app: &tauri::AppHandle, label: &str) {
+    let bridge = app.state::<ForumBridge>();
+    bridge.spa_urls.lock().unwrap_or_else(|e| e.into_inner()).remove(label);
+}
+
+/// Handle a bridge navigation from on_navigation.
+/// Returns true if the URL belonged to the bridge (the navigation must then be cancelled).
+pub fn handle_navigation(app: &tauri::AppHandle, label: &str, url: &Url) -> bool {
+    if !url.as_str().starts_with(BRIDGE_URL_PREFIX) {
+        return false;
+    }
+
+    let params: HashMap<String, String> = url
+        .query_pairs()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    if url.path() == "/spa-navigate" {
+        if let Some(page_url) = params.get("url") {
+            handle_spa_navigation(app, label, page_url);
+        }
+        return true;
+    }
+
+    let id = params.get("id").and_then(|v| v.parse::<u64>().ok());
+    if let (Some(id), Some(data)) = (id, params.get("data")) {
+        let bridge = app.state::<ForumBridge>();
+        let sender = bridge.pending.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+        match sender {
+            Some(tx) => {
+                let _ = tx.send(data.clone());
+            }
+            None => log::warn!("[ForumBridge] Result for unknown or expired request {}", id),
+        }
+    }
+    true
+}
+
+/// Client-side (pushState/popstate) navigation: remember the URL and re-apply the injected
+/// tab bar and theme, which don't get a page load event
+fn handle_spa_navigation(app: &tauri::AppHandle, label: &str, page_url: &str) {
+    log::info!("[ForumBridge] In-page navigation in {}: {}", label, page_url);
+    let bridge = app.state::<ForumBridge>();
+    bridge
+        .spa_urls
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(label.to_string(), page_url.to_string());
+    let _ = app.emit("forum-url-changed", UrlChanged {
+        label: label.to_string(),
+        url: page_url.to_string(),
+    });
+
+    // Don't eval from inside the navigation handler
+    if let Some(window) = app.get_webview_window(label) {
+        tauri::async_runtime::spawn(async move {
+            crate::inject_page_scripts(&window, "SPA");
+        });
+    }
+}
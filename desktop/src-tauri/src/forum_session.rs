@@ -0,0 +1,222 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::Manager;
+use url::Url;
+
+use crate::{settings, ForumTab};
+
+/// Managed state holding tab URLs waiting to be opened in the next forum page load
+#[derive(Default)]
+pub struct ForumSession {
+    /// Saved tabs to restore in the background
+    pending: Mutex<Vec<String>>,
+    /// Tabs requested by forum_open_tabs while the window was still opening
+    pending_open: Mutex<Vec<String>>,
+}
+
+/// Per-URL outcome of forum_open_tabs
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct TabOpenResult {
+    pub url: String,
+    /// The tab was created in the forum window's tab manager
+    pub opened: bool,
+    /// The tab manager isn't ready yet; the tab opens with the next forum page load
+    pub queued: bool,
+    pub error: Option<String>,
+}
+
+/// Get session file path
+fn get_session_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+
+    // Ensure directory exists
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+
+    Ok(app_data_dir.join("forum_tabs.json"))
+}
+
+/// Keep only tabs whose URL passes the navigation allowlist
+fn allowed_urls(tabs: &[ForumTab]) -> Vec<String> {
+    tabs.iter()
+        .filter_map(|tab| Url::parse(&tab.url).ok())
+        .filter(crate::is_allowed_navigation)
+        .filter(|url| url.scheme() != "about")
+        .map(|url| url.to_string())
+        .collect()
+}
+
+/// Build the script that opens restored tabs in the background,
+/// skipping the page that is already showing in the first tab
+fn restore_script(urls: &[String]) -> String {
+    let calls: String = urls
+        .iter()
+        .map(|url| {
+            format!(
+                "if (window.location.href !== '{0}') window.__xprojTabs.createTab('{0}', false);",
+                crate::escape_js_string(url)
+            )
+        })
+        .collect();
+    format!("if (window.__xprojTabs) {{ {} }}", calls)
+}
+
+/// Build the script that opens tabs, switching to the first one
+fn open_script(urls: &[String]) -> String {
+    let calls: String = urls
+        .iter()
+        .enumerate()
+        .map(|(i, url)| format!("window.__xprojTabs.createTab('{}', {});", crate::escape_js_string(url), i == 0))
+        .collect();
+    format!("if (window.__xprojTabs) {{ {} }}", calls)
+}
+
+/// Save the forum window's open tabs so they can be restored on next launch
+pub async fn save_session(app: &tauri::AppHandle, window: &tauri::WebviewWindow) -> Result<(), String> {
+    let tabs = crate::read_forum_tabs(app, window).await?;
+    if tabs.is_empty() {
+        return Ok(());
+    }
+    let json = serde_json::to_string_pretty(&tabs)
+        .map_err(|e| format!("Serialization failed: {}", e))?;
+    let path = get_session_path(app)?;
+    fs::write(&path, json).map_err(|e| format!("Failed to save forum session: {}", e))?;
+    log::info!("[ForumSession] Saved {} tab(s)", tabs.len());
+    Ok(())
+}
+
+/// Queue the saved tabs for restore into the forum window that is about to open
+pub fn queue_restore(app: &tauri::AppHandle) {
+    if !settings::current(app).restore_forum_tabs {
+        return;
+    }
+    let tabs: Vec<ForumTab> = get_session_path(app)
+        .ok()
+        .filter(|path| path.exists())
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    let urls = allowed_urls(&tabs);
+    if urls.is_empty() {
+        return;
+    }
+    let session = app.state::<ForumSession>();
+    *session.pending.lock().unwrap_or_else(|e| e.into_inner()) = urls;
+}
+
+/// Open any queued tabs in the forum window (called after the tab manager is injected)
+pub fn apply_pending(window: &tauri::WebviewWindow) {
+    let session = window.app_handle().state::<ForumSession>();
+    let urls = std::mem::take(&mut *session.pending.lock().unwrap_or_else(|e| e.into_inner()));
+    if !urls.is_empty() {
+        if let Err(e) = window.eval(restore_script(&urls)) {
+            log::warn!("[ForumSession] Failed to restore tabs: {}", e);
+        }
+    }
+    let urls = std::mem::take(&mut *session.pending_open.lock().unwrap_or_else(|e| e.into_inner()));
+    if !urls.is_empty() {
+        if let Err(e) = window.eval(open_script(&urls)) {
+            log::warn!("[ForumSession] Failed to open tabs: {}", e);
+        }
+    }
+}
+
+/// Open the given tabs in the forum window, skipping URLs outside the navigation allowlist.
+/// Returns how many tabs were restored.
+#[tauri::command]
+pub async fn forum_restore_tabs(app: tauri::AppHandle, tabs: Vec<ForumTab>) -> Result<usize, String> {
+    if let Some(window) = app.get_webview_window("forum") {
+        let urls = allowed_urls(&tabs);
+        window.eval(restore_script(&urls)).map_err(|e| e.to_string())?;
+        Ok(urls.len())
+    } else {
+        Err("论坛窗口未打开".to_string())
+    }
+}
+
+/// Check one forum_open_tabs URL against the navigation allowlist and the blocklist
+fn check_open_url(app: &tauri::AppHandle, input: &str) -> Result<String, String> {
+    let url = crate::normalize_url(input)?;
+    if !crate::is_allowed_navigation(&url) || url.scheme() == "about" {
+        return Err(format!("{}: 不允许访问该网址: {}", crate::NAVIGATION_BLOCKED, url));
+    }
+    if crate::url_blocklist::check_navigation(app, &url) {
+        return Err(format!("{}: 该网址已被列入黑名单: {}", crate::NAVIGATION_BLOCKED, url));
+    }
+    Ok(url.to_string())
+}
+
+/// Whether the tab manager is injected in the window's current page. A probe rather than
+/// evaluating the tab calls with a result: the bridge reply would cancel the tab's navigation.
+async fn tab_manager_ready(app: &tauri::AppHandle, window: &tauri::WebviewWindow) -> bool {
+    match crate::forum_bridge::eval_with_result(app, window, "!!window.__xprojTabs").await {
+        Ok(data) => data == "true",
+        Err(e) => {
+            log::warn!("[ForumSession] Tab manager probe failed: {}", e);
+            false
+        }
+    }
+}
+
+/// Open several URLs as forum tabs in one go, switching to the first. The forum window is
+/// opened if needed; URLs outside the navigation allowlist are skipped and reported per URL.
+/// When the tab manager isn't ready (window opening or page loading) the tabs are queued for
+/// the next page load and reported as queued instead of opened.
+#[tauri::command]
+pub async fn forum_open_tabs(app: tauri::AppHandle, urls: Vec<String>) -> Result<Vec<TabOpenResult>, String> {
+    if settings::current(&app).tab_bar_disabled {
+        return Err("标签栏已禁用".to_string());
+    }
+    let mut results = Vec::new();
+    let mut accepted = Vec::new();
+    for input in urls {
+        match check_open_url(&app, &input) {
+            Ok(url) => {
+                accepted.push(url.clone());
+                results.push(TabOpenResult { url, opened: false, queued: false, error: None });
+            }
+            Err(e) => results.push(TabOpenResult { url: input, opened: false, queued: false, error: Some(e) }),
+        }
+    }
+    if accepted.is_empty() {
+        return Ok(results);
+    }
+
+    let session = app.state::<ForumSession>();
+    let opened = match app.get_webview_window("forum") {
+        Some(window) if tab_manager_ready(&app, &window).await => {
+            window.eval(open_script(&accepted)).map_err(|e| e.to_string())?;
+            true
+        }
+        Some(_) => {
+            // apply_pending opens them once the tab manager is injected into the loading page
+            session.pending_open.lock().unwrap_or_else(|e| e.into_inner()).extend(accepted);
+            false
+        }
+        None => {
+            // The tab manager only exists once the first page loads, so the tabs wait for apply_pending
+            session.pending_open.lock().unwrap_or_else(|e| e.into_inner()).extend(accepted);
+            if let Err(e) = crate::open_forum_window(app.clone()).await {
+                session.pending_open.lock().unwrap_or_else(|e| e.into_inner()).clear();
+                return Err(e);
+            }
+            false
+        }
+    };
+    for result in results.iter_mut().filter(|result| result.error.is_none()) {
+        result.opened = opened;
+        result.queued = !opened;
+    }
+    Ok(results)
+}
+
+/// Enable or disable restoring forum tabs across restarts
+#[tauri::command]
+pub async fn set_forum_tab_restore(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    settings::update(&app, |s| s.restore_forum_tabs = enabled)?;
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use tauri::Manager;
+
+use crate::settings;
+
+/// Predefined themes injected into forum/browser pages (name, CSS)
+const THEMES: [(&str, &str); 3] = [
+    (
+        "dark",
+        r#"
+html { background: #1a202c !important; }
+body, #wp, .wp, .bm, .bm_c, .bm_h, .fl, .tl, .pl, .ct2, #ct {
+    background-color: #1a202c !important;
+    color: #e2e8f0 !important;
+    border-color: #4a5568 !important;
+}
+a { color: #90cdf4 !important; }
+input, textarea, select {
+    background-color: #2d3748 !important;
+    color: #e2e8f0 !important;
+    border-color: #4a5568 !important;
+}
+img { opacity: 0.9; }
+"#,
+    ),
+    (
+        "high-contrast",
+        r#"
+body, #wp, .wp, .bm, .bm_c, .bm_h, .fl, .tl, .pl, .ct2, #ct {
+    background-color: #000 !important;
+    color: #fff !important;
+    border-color: #fff !important;
+}
+a { color: #ff0 !important; text-decoration: underline !important; }
+a:visited { color: #0ff !important; }
+input, textarea, select, button {
+    background-color: #000 !important;
+    color: #fff !important;
+    border: 2px solid #fff !important;
+}
+"#,
+    ),
+    (
+        "compact",
+        r#"
+body { font-size: 12px !important; line-height: 1.4 !important; }
+.bm, .bm_c, .bm_h, .fl, .tl, .pl { padding: 2px 4px !important; margin-bottom: 4px !important; }
+.tl th, .tl td { padding: 2px 4px !important; }
+.pl .t_f { font-size: 13px !important; }
+"#,
+    ),
+];
+
+/// Look up a theme's CSS by name
+fn theme_css(name: &str) -> Option<&'static str> {
+    THEMES.iter().find(|(n, _)| *n == name).map(|(_, css)| *css)
+}
+
+/// Build the script that replaces the injected theme stylesheet (empty CSS removes it)
+fn theme_script(css: &str) -> String {
+    format!(
+        r#"(function() {{
+    var old = document.getElementById('xproj-theme');
+    if (old) old.remove();
+    var css = '{}';
+    if (!css || !document.head) return;
+    var style = document.createElement('style');
+    style.id = 'xproj-theme';
+    style.textContent = css;
+    document.head.appendChild(style);
+}})();"#,
+        crate::escape_js_string(css)
+    )
+}
+
+/// Inject the selected theme into a page (called on every page load so navigation keeps it)
+pub fn apply(window: &tauri::WebviewWindow) {
+    let css = settings::current(window.app_handle())
+        .forum_theme
+        .as_deref()
+        .and_then(theme_css)
+        .unwrap_or("");
+    if css.is_empty() {
+        return;
+    }
+    if let Err(e) = window.eval(theme_script(css)) {
+        log::warn!("[ForumTheme] Failed to inject theme: {}", e);
+    }
+}
+
+/// List the available forum theme names
+#[tauri::command]
+pub async fn forum_list_themes() -> Result<Vec<String>, String> {
+    Ok(THEMES.iter().map(|(name, _)| name.to_string()).collect())
+}
+
+/// Select a forum theme (None or empty clears it) and apply it to the open forum window
+#[tauri::command]
+pub async fn forum_set_theme(app: tauri::AppHandle, name: Option<String>) -> Result<(), String> {
+    let name = name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    let css = match name.as_deref() {
+        Some(n) => theme_css(n).ok_or_else(|| format!("未知主题: {}", n))?,
+        None => "",
+    };
+
+    settings::update(&app, |s| s.forum_theme = name)?;
+    if let Some(window) = app.get_webview_window("forum") {
+        window.eval(theme_script(css)).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use maxminddb::{geoip2, Reader};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::Manager;
+
+use crate::dns_cache;
+
+/// Country database in the app data directory. GeoLite2 can't be redistributed with the app,
+/// so the user provides it through set_geoip_database.
+const GEOIP_DATABASE_FILE: &str = "GeoLite2-Country.mmdb";
+
+/// Result of server_flag; both fields are empty when the country is unknown
+#[derive(serde::Serialize, Clone, Debug, Default)]
+pub struct ServerFlag {
+    /// Regional indicator pair, e.g. "🇨🇳"
+    pub flag: String,
+    /// ISO 3166-1 alpha-2 code, e.g. "CN"
+    pub country_code: String,
+}
+
+#[derive(Default)]
+struct GeoIpState {
+    /// Opened on first use; None while the database file is missing
+    reader: Option<Reader<Vec<u8>>>,
+    flags: HashMap<IpAddr, ServerFlag>,
+}
+
+/// Managed state holding the country database and the per-IP flag cache
+#[derive(Default)]
+pub struct GeoIp(Mutex<GeoIpState>);
+
+fn get_database_path(app: &tauri::AppHandle) -> Option<PathBuf> {
+    app.path().app_data_dir().ok().map(|dir| dir.join(GEOIP_DATABASE_FILE))
+}
+
+/// Flag emoji for a two-letter country code (empty for anything else)
+fn country_flag(country_code: &str) -> String {
+    if country_code.len() != 2 || !country_code.bytes().all(|b| b.is_ascii_alphabetic()) {
+        return String::new();
+    }
+    country_code
+        .to_ascii_uppercase()
+        .chars()
+        .filter_map(|c| char::from_u32(0x1F1E6 + (c as u32 - 'A' as u32)))
+        .collect()
+}
+
+/// Look up an address in the country database, opening it on first use. Blocking.
+fn lookup(app: &tauri::AppHandle, ip: IpAddr) -> ServerFlag {
+    let geoip = app.state::<GeoIp>();
+    let mut state = geoip.0.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(flag) = state.flags.get(&ip) {
+        return flag.clone();
+    }
+    if state.reader.is_none() {
+        let path = match get_database_path(app) {
+            Some(path) if path.exists() => path,
+            // Not cached, so flags appear once a database is provided
+            _ => return ServerFlag::default(),
+        };
+        match Reader::open_readfile(&path) {
+            Ok(reader) => state.reader = Some(reader),
+            Err(e) => {
+                log::warn!("[GeoIP] Failed to open {}: {}", path.display(), e);
+                return ServerFlag::default();
+            }
+        }
+    }
+
+    let country_code = state
+        .reader
+        .as_ref()
+        .and_then(|reader| reader.lookup::<geoip2::Country>(ip).ok())
+        .and_then(|record| record.country.or(record.registered_country))
+        .and_then(|country| country.iso_code)
+        .unwrap_or_default()
+        .to_ascii_uppercase();
+    let flag = ServerFlag {
+        flag: country_flag(&country_code),
+        country_code,
+    };
+    state.flags.insert(ip, flag.clone());
+    flag
+}
+
+/// Country flag emoji and code of a server address (IP or hostname), cached per IP.
+/// Empty for private or unresolvable addresses and while no country database is installed.
+#[tauri::command]
+pub async fn server_flag(app: tauri::AppHandle, ip: String) -> Result<ServerFlag, String> {
+    tokio::task::spawn_blocking(move || {
+        let addr = match dns_cache::resolve(&app.state::<dns_cache::DnsCache>(), &ip) {
+            Ok(addr) => addr,
+            Err(_) => return ServerFlag::default(),
+        };
+        if crate::is_local_address(addr) {
+            return ServerFlag::default();
+        }
+        lookup(&app, addr)
+    })
+    .await
+    .map_err(|e| format!("GeoIP task failed: {}", e))
+}
+
+/// Install a MaxMind country or city database (e.g. GeoLite2-Country.mmdb) for server_flag.
+/// The file must be in one of the directories check_user_file_path allows.
+#[tauri::command]
+pub async fn set_geoip_database(app: tauri::AppHandle, path: String) -> Result<(), String> {
+    let path = crate::check_user_file_path(&app, &path, &["mmdb"])?;
+    tokio::task::spawn_blocking(move || {
+        let reader = Reader::open_readfile(&path).map_err(|e| format!("无效的 GeoIP 数据库: {}", e))?;
+        // City databases carry the country records too
+        let database_type = reader.metadata.database_type.clone();
+        if !database_type.contains("Country") && !database_type.contains("City") {
+            return Err(format!("不支持的 GeoIP 数据库类型: {}", database_type));
+        }
+        let target = get_database_path(&app).ok_or_else(|| "Failed to get app data dir".to_string())?;
+        if let Some(dir) = target.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create app data dir: {}", e))?;
+        }
+        std::fs::copy(&path, &target).map_err(|e| format!("Failed to install GeoIP database: {}", e))?;
+
+        let geoip = app.state::<GeoIp>();
+        let mut state = geoip.0.lock().unwrap_or_else(|e| e.into_inner());
+        state.reader = Some(reader);
+        state.flags.clear();
+        log::info!("[GeoIP] Installed {} database", database_type);
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("GeoIP task failed: {}", e))?
+}
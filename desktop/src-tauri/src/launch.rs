@@ -0,0 +1,70 @@
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+use tauri_plugin_deep_link::DeepLinkExt;
+use url::Url;
+
+use crate::JoinRequest;
+
+/// Managed state holding the connect link the app was launched with, until the frontend takes it
+#[derive(Default)]
+pub struct LaunchJoin(Mutex<Option<JoinRequest>>);
+
+/// Server address of a connect link: xproj://connect/ip:port or steam://connect/ip:port.
+/// Anything else (including the internal xproj://forum/ and xproj://auth/ URLs) is ignored.
+pub fn connect_target(url: &Url) -> Option<JoinRequest> {
+    if !matches!(url.scheme(), "xproj" | "steam") || url.host_str() != Some("connect") {
+        return None;
+    }
+    let (ip, port) = crate::parse_server_address(url.path().trim_start_matches('/'))?;
+    Some(JoinRequest { ip, port: port.to_string(), name: None })
+}
+
+/// Connect link among command-line arguments; unrelated arguments are skipped
+fn connect_target_in_args(args: impl Iterator<Item = String>) -> Option<JoinRequest> {
+    args.filter(|arg| arg.contains("://"))
+        .filter_map(|arg| Url::parse(&arg).ok())
+        .find_map(|url| connect_target(&url))
+}
+
+/// Keep a join request for the frontend and announce it as launch-join-request
+pub fn queue(app: &tauri::AppHandle, join: JoinRequest, announce: bool) {
+    log::info!("[Launch] Connect link for {}:{}", join.ip, join.port);
+    *app.state::<LaunchJoin>().0.lock().unwrap_or_else(|e| e.into_inner()) = Some(join.clone());
+    if announce {
+        let _ = app.emit("launch-join-request", join);
+    }
+}
+
+/// Register the xproj:// handler and pick up a connect link from the launch arguments.
+/// The link is queued rather than emitted, since the frontend isn't listening yet during setup;
+/// links opened while the app runs (macOS delivers them as events, other platforms through a
+/// second launch, see handle_second_instance) are emitted right away.
+pub fn init(app: &tauri::AppHandle) {
+    // Installers register the scheme; this covers unregistered installs like a bare AppImage
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(e) = app.deep_link().register_all() {
+        log::warn!("[Launch] Failed to register the xproj:// handler: {}", e);
+    }
+
+    let current = app.deep_link().get_current().ok().flatten().unwrap_or_default();
+    let launch_join = current
+        .iter()
+        .find_map(connect_target)
+        .or_else(|| connect_target_in_args(std::env::args().skip(1)));
+    if let Some(join) = launch_join {
+        queue(app, join, false);
+    }
+
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        if let Some(join) = event.urls().iter().find_map(connect_target) {
+            queue(&handle, join, true);
+        }
+    });
+}
+
+/// Take the connect link the app was launched with (None if there was none or it was taken)
+#[tauri::command]
+pub async fn take_launch_join_request(app: tauri::AppHandle) -> Result<Option<JoinRequest>, String> {
+    Ok(app.state::<LaunchJoin>().0.lock().unwrap_or_else(|e| e.into_inner()).take())
+}
@@ -1,11 +1,63 @@
 use tauri::Manager;
 use tauri::Emitter;
 use url::Url;
-use std::net::UdpSocket;
-use std::time::Duration;
+use std::net::{IpAddr, SocketAddr, UdpSocket};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use a2s::ChallengeReply;
 
 // Secure credential storage module
 mod secure_storage;
+// Persistent application settings module
+mod settings;
+// Hostname resolution cache module
+mod dns_cache;
+// Steam client detection and game launch module
+mod steam;
+// Extended A2S queries (rules) and server logo cache module
+mod a2s;
+// Per-server query history store module
+mod server_store;
+// Short-lived A2S result cache module
+mod a2s_cache;
+// Diagnostics bundle export module
+mod diagnostics;
+// JS -> Rust result bridge for forum/browser windows
+mod forum_bridge;
+// Forum tab session save/restore module
+mod forum_session;
+// Predefined forum CSS themes module
+mod forum_theme;
+// Favorite servers and background refresh module
+mod favorites;
+// OS user authentication prompt (Windows Hello) module
+mod os_auth;
+// System clock skew detection module
+mod clock;
+// Backend server list module
+mod server_list;
+// Forum daily check-in result detection module
+mod checkin;
+// Backend A2S relay module
+mod query_relay;
+// Navigation blocklist module
+mod url_blocklist;
+// Battery-aware power saving module
+mod power;
+// Passphrase-encrypted backup/restore of all app data
+mod backup;
+// Process and webview memory usage module
+mod memory;
+// Favorite server transition notifications
+mod notifications;
+// Prometheus-style metrics export of query results
+mod metrics;
+// Country flags for server addresses from a MaxMind GeoIP database
+mod geoip;
+// xproj:// / steam://connect launch links for auto-join
+mod launch;
+// Ring buffer of recent failed queries for diagnosing flaky servers
+mod query_failures;
 
 // Browser User-Agent for WebView windows (configured at compile-time via XPROJ_BROWSER_USER_AGENT env var)
 // Default: 'XProj-Desktop-Browser/1.0.0 (+https://servers.upkk.com) Chrome/120.0.0.0'
@@ -22,52 +74,167 @@ const A2S_INFO: [u8; 25] = [
     0x00
 ];
 
+// Largest UDP payload a server can send back (protocol max); responses are read into a buffer this size
+const A2S_MAX_PACKET_SIZE: usize = 65507;
+
+// Default A2S socket timeout (matching backend), overridable per server
+const A2S_DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Error code for servers that issue a challenge but never answer the resent query
+const CHALLENGE_NO_RESPONSE: &str = "ChallengeNoResponse";
+
+// Server population classification, lets the frontend sort/filter on a single field
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PopulationState {
+    #[default]
+    Empty,
+    Low,
+    Active,
+    NearFull,
+    Full,
+}
+
 // A2S query result structure matching the TypeScript ServerStatus interface
-#[derive(serde::Serialize, Clone, Debug)]
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(default)]
 pub struct A2SQueryResult {
     pub success: bool,
     pub error: Option<String>,
     pub ip: String,
     pub port: String,
     pub name: String,
+    // name with control characters and undecodable bytes removed, cut to SERVER_NAME_MAX_CHARS
+    pub name_sanitized: String,
+    // name looks like garbage: mostly control/undecodable bytes or absurdly long (raw value kept in name)
+    pub name_suspect: bool,
     pub map_name: String,
     pub game: String,
+    // Steam App ID from A2S_INFO (730 for both CS2 and CS:GO)
+    pub app_id: u16,
+    // Engine derived from app_id and version: "cs2", "csgo", "css", "tf2", "gmod", "l4d2" or "source"
+    pub engine: String,
     pub players: i32,
     pub max_players: i32,
     pub bots: i32,
     pub real_players: i32,
+    // Every reported player is a bot (players > 0, real_players == 0): practice/bot servers
+    pub bots_only: bool,
+    // Humans counted from A2S_PLAYER entries (see a2s::count_humans), None when player data wasn't fetched
+    // real_players is only players - bots, which still includes SourceTV and some plugin bots
+    pub human_players: Option<i32>,
     pub server_type: String,
+    // Server is a SourceTV relay (server type 'p'): spectating only, not joinable for play
+    pub is_sourcetv: bool,
     pub environment: String,
     pub password: bool,
     pub vac: bool,
     pub version: String,
+    pub population_state: PopulationState,
+    // Reported counts look fake: players > max, bots > players, or an implausible jump since the last query
+    pub suspicious_count: bool,
+    // Round-trip time of the final A2S_INFO request in milliseconds
+    pub ping_ms: u32,
+    // Response filled the receive buffer or was split across packets, so parsed fields may be incomplete
+    pub truncated: bool,
+    // Address family the successful query went over ("IPv4" / "IPv6")
+    pub address_family: Option<String>,
+    // Machine-readable failure reason for errors the UI handles specially (e.g. "NetworkPaused")
+    pub error_code: Option<String>,
+    // How the result was obtained: "udp" (local query), "relay" (queried by the backend) or "tcp"
+    pub query_path: Option<String>,
+    // Per-phase timing breakdown, only filled when requested with include_timings
+    pub timings_ms: Option<A2STimings>,
+    // Exact bytes exchanged, only filled in devtools builds when requested with include_raw
+    pub raw: Option<A2SRawExchange>,
+    // Whether ip:port is in the saved favorites, set by query_server_a2s
+    pub is_favorite: bool,
+    // Reverse DNS hostname of the queried address, only filled when requested with include_rdns
+    pub rdns: Option<String>,
+    // Replies from an address other than the queried one arrived and were discarded
+    // (spoofed or misrouted packets; only detectable on pooled, unconnected sockets)
+    pub source_mismatch: bool,
+}
+
+// Raw request/response trace of an A2S_INFO query (base64), for protocol debugging
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct A2SRawExchange {
+    // Every request sent, in order: the initial A2S_INFO (+ challenge) and the challenge resend if any
+    pub requests_base64: Vec<String>,
+    // Final response received (captured before validation, so malformed replies show up too)
+    pub response_base64: Option<String>,
 }
 
-impl Default for A2SQueryResult {
-    fn default() -> Self {
-        A2SQueryResult {
-            success: false,
-            error: None,
-            ip: String::new(),
-            port: String::new(),
-            name: String::new(),
-            map_name: String::new(),
-            game: String::new(),
-            players: 0,
-            max_players: 0,
-            bots: 0,
-            real_players: 0,
-            server_type: String::new(),
-            environment: String::new(),
-            password: false,
-            vac: false,
-            version: String::new(),
+// Optional extras collected by a2s_query
+#[derive(Clone, Copy, Debug, Default)]
+struct QueryDebug {
+    timings: bool,
+    // Ignored outside devtools builds
+    raw: bool,
+    // Reverse DNS lookup of the queried address (done by query_server, not a2s_query)
+    rdns: bool,
+}
+
+// Receive timeout of one query: the per-reply timeout, cut short by a batch deadline if any
+// A query abandoned by its batch runs on in the blocking pool, so it has to stop by itself
+#[derive(Clone, Copy, Debug)]
+struct QueryTimeout {
+    per_reply: Duration,
+    deadline: Option<Instant>,
+}
+
+impl QueryTimeout {
+    // Read timeout for the next reply, None once the deadline has passed
+    fn next_reply(&self) -> Option<Duration> {
+        match self.deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                (!remaining.is_zero()).then(|| self.per_reply.min(remaining))
+            }
+            None => Some(self.per_reply),
         }
     }
 }
 
+// Result for a query stopped by its batch deadline
+fn deadline_exceeded(mut result: A2SQueryResult) -> A2SQueryResult {
+    result.error = Some(format!("{}: 查询超出批量刷新时限", DEADLINE_EXCEEDED));
+    result.error_code = Some(DEADLINE_EXCEEDED.to_string());
+    result
+}
+
+// Everything a2s_query_timed records besides the result itself
+#[derive(Default)]
+struct QueryTrace {
+    timings: A2STimings,
+    requests: Vec<Vec<u8>>,
+    response: Vec<u8>,
+    // The query timed out or the address had no route, so another address family may still work
+    unanswered: bool,
+}
+
+// Time spent in each phase of an A2S_INFO query, in milliseconds
+// Phases that weren't reached (query failed earlier, no challenge) stay at 0 / None
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct A2STimings {
+    pub socket_create: f64,
+    pub connect: f64,
+    pub send: f64,
+    pub first_recv: f64,
+    pub challenge_round_trip: Option<f64>,
+    pub parse: f64,
+}
+
+// Milliseconds elapsed since an Instant, with sub-millisecond precision
+fn elapsed_ms(since: Instant) -> f64 {
+    since.elapsed().as_secs_f64() * 1000.0
+}
+
 // Helper function to read null-terminated string from buffer
 fn read_cstring(data: &[u8], start: usize) -> (String, usize) {
+    // Responses are parsed up to the received length, so a missing terminator can push start past the end
+    let start = start.min(data.len());
     let mut end = start;
     while end < data.len() && data[end] != 0 {
         end += 1;
@@ -76,51 +243,225 @@ fn read_cstring(data: &[u8], start: usize) -> (String, usize) {
     (s, end + 1) // +1 to skip the null terminator
 }
 
+// Server names longer than this are flagged as suspect (the hostname cvar normally caps them at 64 bytes)
+const SERVER_NAME_MAX_CHARS: usize = 128;
+
+// Share of control characters and undecodable bytes above which a server name is flagged as suspect
+const SERVER_NAME_MAX_GARBAGE_RATIO: f64 = 0.2;
+
+// Characters that render as garbage: control characters and the replacement character from lossy decoding
+fn is_garbage_char(c: char) -> bool {
+    c.is_control() || c == char::REPLACEMENT_CHARACTER
+}
+
+// Displayable version of a server name and whether the raw name looks like garbage
+fn check_server_name(name: &str) -> (String, bool) {
+    let total = name.chars().count();
+    let garbage = name.chars().filter(|c| is_garbage_char(*c)).count();
+    let suspect = total > SERVER_NAME_MAX_CHARS
+        || (total > 0 && garbage as f64 / total as f64 > SERVER_NAME_MAX_GARBAGE_RATIO);
+    let sanitized: String = name
+        .chars()
+        .filter(|c| !is_garbage_char(*c))
+        .take(SERVER_NAME_MAX_CHARS)
+        .collect();
+    (sanitized.trim().to_string(), suspect)
+}
+
+// Split-packet responses start with 0xFFFFFFFE instead of 0xFFFFFFFF
+fn is_split_packet(buf: &[u8]) -> bool {
+    buf.len() >= 4 && buf[..4] == [0xFE, 0xFF, 0xFF, 0xFF]
+}
+
+// Mark a result as truncated because the server split its response across packets
+fn split_packet_result(mut result: A2SQueryResult, n: usize) -> A2SQueryResult {
+    log::warn!(
+        "[A2S] Oversized split-packet response from {}:{} ({} bytes in first packet), needs split-packet handling",
+        result.ip, result.port, n
+    );
+    result.truncated = true;
+    result.error = Some("Split-packet response not supported".to_string());
+    result
+}
+
 // Perform A2S_INFO query to a game server
 // This is the local UDP implementation matching the backend Go logic
-fn a2s_query(ip: &str, port: &str) -> A2SQueryResult {
-    let mut result = A2SQueryResult::default();
-    result.ip = ip.to_string();
-    result.port = port.to_string();
-    
-    let address = format!("{}:{}", ip, port);
+// If source_ip is set, the socket is bound to that local address to force a specific interface
+// debug selects extras for the result: a per-phase timing breakdown and (devtools) the raw bytes
+// Challenge numbers are reused from (and stored in) the challenge cache
+// Also returns whether the query went unanswered (timeout or no route) rather than failing outright
+fn a2s_query(
+    ip: &str,
+    port: &str,
+    source_ip: Option<IpAddr>,
+    timeout: QueryTimeout,
+    challenges: &a2s::ChallengeCache,
+    pool: Option<&a2s::SocketPool>,
+    debug: QueryDebug,
+) -> (A2SQueryResult, bool) {
+    use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine as _};
+
+    let mut trace = QueryTrace::default();
+    let mut result = a2s_query_timed(ip, port, source_ip, timeout, challenges, pool, &mut trace);
+    if debug.timings {
+        result.timings_ms = Some(trace.timings);
+    }
+    if debug.raw && cfg!(feature = "devtools") {
+        result.raw = Some(A2SRawExchange {
+            requests_base64: trace.requests.iter().map(|r| BASE64_STANDARD.encode(r)).collect(),
+            response_base64: (!trace.response.is_empty()).then(|| BASE64_STANDARD.encode(&trace.response)),
+        });
+    }
+    (result, trace.unanswered)
+}
+
+fn a2s_query_timed(
+    ip: &str,
+    port: &str,
+    source_ip: Option<IpAddr>,
+    timeout: QueryTimeout,
+    challenges: &a2s::ChallengeCache,
+    pool: Option<&a2s::SocketPool>,
+    trace: &mut QueryTrace,
+) -> A2SQueryResult {
+    let timings = &mut trace.timings;
+    let mut result = A2SQueryResult {
+        ip: ip.to_string(),
+        port: port.to_string(),
+        ..Default::default()
+    };
+    let reply_timeout = match timeout.next_reply() {
+        Some(t) => t,
+        None => return deadline_exceeded(result),
+    };
     
-    // Create UDP socket
-    let socket = match UdpSocket::bind("0.0.0.0:0") {
-        Ok(s) => s,
-        Err(e) => {
-            result.error = Some(format!("Failed to create socket: {}", e));
-            return result;
+    // Create UDP socket (bound to the configured source address if any) and set timeout,
+    // or take the next one from the batch's source port pool
+    // Pooled sockets are shared between servers and stay unconnected, so they send to the
+    // server's address and check where replies come from instead
+    let phase = Instant::now();
+    let pooled;
+    let fresh;
+    let pooled_peer = pool
+        .filter(|p| p.matches(ip, source_ip))
+        .and_then(|p| Some((p, SocketAddr::new(ip.parse().ok()?, port.parse().ok()?))));
+    let (socket, peer): (&UdpSocket, a2s::Peer) = match pooled_peer {
+        Some((pool, addr)) => {
+            pooled = pool.acquire();
+            if let Err(e) = pooled.set_read_timeout(Some(reply_timeout)) {
+                result.error = Some(format!("Failed to set timeout: {}", e));
+                return result;
+            }
+            (&pooled, a2s::Peer::Unconnected(addr))
         }
+        None => match a2s::bind_socket(ip, source_ip, reply_timeout) {
+            Ok(s) => {
+                fresh = s;
+                (&fresh, a2s::Peer::Connected)
+            }
+            Err(e) => {
+                result.error = Some(e);
+                return result;
+            }
+        },
     };
-    
-    // Set timeout (5 seconds, matching backend)
-    if let Err(e) = socket.set_read_timeout(Some(Duration::from_secs(5))) {
-        result.error = Some(format!("Failed to set timeout: {}", e));
-        return result;
-    }
+    timings.socket_create = elapsed_ms(phase);
     
     // Connect to server
-    if let Err(e) = socket.connect(&address) {
-        result.error = Some(format!("Failed to connect: {}", e));
-        return result;
+    let phase = Instant::now();
+    if let a2s::Peer::Connected = peer {
+        if let Err(e) = a2s::connect_to_io(socket, ip, port) {
+            trace.unanswered = a2s::is_unreachable(&e);
+            result.error = Some(format!("Failed to connect: {}", e));
+            return result;
+        }
     }
+    timings.connect = elapsed_ms(phase);
     
-    // Send A2S_INFO query
-    if let Err(e) = socket.send(&A2S_INFO) {
-        result.error = Some(format!("Failed to send query: {}", e));
-        return result;
+    // Send A2S_INFO query, with the server's cached challenge if we have one
+    let cached = a2s::cached_challenge(challenges, ip, port, &A2S_INFO);
+    let mut sent_at = Instant::now();
+    match a2s::send_with_challenge_to(socket, peer, &A2S_INFO, cached.unwrap_or(a2s::CHALLENGE_REQUEST)) {
+        Ok(request) => trace.requests.push(request),
+        Err(e) => {
+            result.error = Some(e);
+            return result;
+        }
     }
+    timings.send = elapsed_ms(sent_at);
     
     // Receive response
-    let mut buf = [0u8; 1400];
-    let n = match socket.recv(&mut buf) {
-        Ok(n) => n,
+    let mut buf = vec![0u8; A2S_MAX_PACKET_SIZE];
+    let mut mismatches = 0;
+    let reply = a2s::recv_reply_from(socket, peer, &mut buf, &mut mismatches);
+    result.source_mismatch = mismatches > 0;
+    let reply = match reply {
+        Ok(reply) => reply,
+        Err(e) if a2s::is_timeout(&e) && timeout.next_reply().is_none() => {
+            return deadline_exceeded(result);
+        }
         Err(e) => {
+            trace.unanswered = a2s::is_timeout(&e) || a2s::is_unreachable(&e);
             result.error = Some(format!("Failed to receive: {}", e));
             return result;
         }
     };
+    timings.first_recv = elapsed_ms(sent_at);
+    
+    let n = match reply {
+        ChallengeReply::Response(n) => n,
+        // Challenge needed (or the cached one expired): resend with the new number
+        ChallengeReply::Challenge(challenge) => {
+            a2s::store_challenge(challenges, ip, port, &A2S_INFO, challenge);
+            
+            // The first reply used up part of the time left before the batch deadline
+            let reply_timeout = match timeout.next_reply() {
+                Some(t) => t,
+                None => return deadline_exceeded(result),
+            };
+            if let Err(e) = socket.set_read_timeout(Some(reply_timeout)) {
+                result.error = Some(format!("Failed to set timeout: {}", e));
+                return result;
+            }
+            
+            // Ping is measured on this round trip instead
+            sent_at = Instant::now();
+            match a2s::send_with_challenge_to(socket, peer, &A2S_INFO, challenge) {
+                Ok(request) => trace.requests.push(request),
+                Err(e) => {
+                    result.error = Some(e);
+                    return result;
+                }
+            }
+            let reply = a2s::recv_reply_from(socket, peer, &mut buf, &mut mismatches);
+            result.source_mismatch = mismatches > 0;
+            timings.challenge_round_trip = Some(elapsed_ms(sent_at));
+            match reply {
+                Ok(ChallengeReply::Response(n)) => n,
+                Ok(ChallengeReply::Challenge(_)) => {
+                    result.error = Some("Server rejected the challenge response".to_string());
+                    return result;
+                }
+                Err(e) if a2s::is_timeout(&e) && timeout.next_reply().is_none() => {
+                    return deadline_exceeded(result);
+                }
+                // The server is up (it issued a challenge) but its query handling is broken
+                Err(e) if a2s::is_timeout(&e) => {
+                    result.error = Some(format!(
+                        "{}: Server sent a challenge but never answered the challenged query",
+                        CHALLENGE_NO_RESPONSE
+                    ));
+                    result.error_code = Some(CHALLENGE_NO_RESPONSE.to_string());
+                    return result;
+                }
+                Err(e) => {
+                    result.error = Some(format!("Failed to receive: {}", e));
+                    return result;
+                }
+            }
+        }
+    };
+    trace.response = buf[..n].to_vec();
     
     if n < 6 {
         result.error = Some("Response too short".to_string());
@@ -128,51 +469,44 @@ fn a2s_query(ip: &str, port: &str) -> A2SQueryResult {
     }
     
     // Check header (0xFFFFFFFF)
+    if is_split_packet(&buf) {
+        return split_packet_result(result, n);
+    }
     if buf[0] != 0xFF || buf[1] != 0xFF || buf[2] != 0xFF || buf[3] != 0xFF {
         result.error = Some("Invalid response header".to_string());
         return result;
     }
     
-    // Check if challenge is needed (response type 'A' = 0x41)
-    if buf[4] == 0x41 && n >= 9 {
-        // Extract challenge number
-        let challenge = u32::from_le_bytes([buf[5], buf[6], buf[7], buf[8]]);
-        
-        // Create challenge request
-        let mut challenge_request = A2S_INFO.to_vec();
-        challenge_request.extend_from_slice(&challenge.to_le_bytes());
-        
-        // Send challenge request
-        if let Err(e) = socket.send(&challenge_request) {
-            result.error = Some(format!("Failed to send challenge: {}", e));
-            return result;
-        }
-        
-        // Receive response again
-        let n2 = match socket.recv(&mut buf) {
-            Ok(n) => n,
-            Err(e) => {
-                result.error = Some(format!("Failed to receive after challenge: {}", e));
-                return result;
-            }
-        };
-        
-        if n2 < 6 {
-            result.error = Some("Response too short after challenge".to_string());
-            return result;
-        }
-        
-        // Check header again
-        if buf[0] != 0xFF || buf[1] != 0xFF || buf[2] != 0xFF || buf[3] != 0xFF {
-            result.error = Some("Invalid response header after challenge".to_string());
-            return result;
-        }
+    result.ping_ms = sent_at.elapsed().as_millis().min(u32::MAX as u128) as u32;
+    
+    // A completely filled buffer means the datagram was likely cut off
+    if n >= buf.len() {
+        result.truncated = true;
+        log::warn!("[A2S] Response from {}:{} filled the {} byte buffer, fields may be incomplete", ip, port, n);
+    }
+    
+    // Only parse the bytes actually received
+    buf.truncate(n);
+    let parse_started = Instant::now();
+    if let Err(e) = parse_a2s_info(&buf, &mut result) {
+        result.error = Some(e);
+        return result;
+    }
+    timings.parse = elapsed_ms(parse_started);
+    result.success = true;
+    result
+}
+
+// Parse an A2S_INFO response (starting at the 0xFFFFFFFF header) into result
+// Shared by the UDP and TCP query paths so both stay in sync
+fn parse_a2s_info(buf: &[u8], result: &mut A2SQueryResult) -> Result<(), String> {
+    if buf.len() < 6 {
+        return Err("Response too short".to_string());
     }
     
     // Verify response type 'I' (0x49) for A2S_INFO response
     if buf[4] != 0x49 {
-        result.error = Some(format!("Invalid response type: 0x{:02X}", buf[4]));
-        return result;
+        return Err(format!("Invalid response type: 0x{:02X}", buf[4]));
     }
     
     // Parse A2S_INFO response
@@ -180,26 +514,31 @@ fn a2s_query(ip: &str, port: &str) -> A2SQueryResult {
     let mut pos = 6; // Start after header, type, and protocol byte
     
     // Parse server name
-    let (name, next_pos) = read_cstring(&buf, pos);
+    let (name, next_pos) = read_cstring(buf, pos);
+    (result.name_sanitized, result.name_suspect) = check_server_name(&name);
+    if result.name_suspect {
+        log::warn!("[A2S] Server name looks malformed ({} bytes)", name.len());
+    }
     result.name = name;
     pos = next_pos;
     
     // Parse map name
-    let (map_name, next_pos) = read_cstring(&buf, pos);
+    let (map_name, next_pos) = read_cstring(buf, pos);
     result.map_name = map_name;
     pos = next_pos;
     
     // Parse folder (skip)
-    let (_, next_pos) = read_cstring(&buf, pos);
+    let (_, next_pos) = read_cstring(buf, pos);
     pos = next_pos;
     
     // Parse game name
-    let (game, next_pos) = read_cstring(&buf, pos);
+    let (game, next_pos) = read_cstring(buf, pos);
     result.game = game;
     pos = next_pos;
     
-    // Parse Steam App ID (2 bytes, little endian) - skip
+    // Parse Steam App ID (2 bytes, little endian)
     if pos + 2 <= buf.len() {
+        result.app_id = u16::from_le_bytes([buf[pos], buf[pos + 1]]);
         pos += 2;
     }
     
@@ -221,6 +560,7 @@ fn a2s_query(ip: &str, port: &str) -> A2SQueryResult {
             b'p' => "sourcetv".to_string(),
             c => format!("{}", c as char),
         };
+        result.is_sourcetv = buf[pos] == b'p';
         pos += 1;
     }
     
@@ -249,10 +589,14 @@ fn a2s_query(ip: &str, port: &str) -> A2SQueryResult {
     
     // Parse version string
     if pos < buf.len() {
-        let (version, _) = read_cstring(&buf, pos);
+        let (version, _) = read_cstring(buf, pos);
         result.version = version;
     }
     
+    // Impossible combinations are flagged before sanitization may zero them
+    result.suspicious_count = (result.max_players > 0 && result.players > result.max_players)
+        || result.bots > result.players;
+    
     // Sanitize unreasonable player counts (matching backend logic)
     // CS2/CSGO servers have max 64 player slots; values >67 indicate corrupt/invalid data
     // This matches the Go backend's A2SInfo() sanitization
@@ -267,23 +611,1124 @@ fn a2s_query(ip: &str, port: &str) -> A2SQueryResult {
     if result.real_players < 0 {
         result.real_players = 0;
     }
+    result.bots_only = is_bots_only(result);
+    
+    result.engine = detect_engine(result.app_id, &result.version).to_string();
+    
+    Ok(())
+}
+
+// Server is populated by bots alone
+fn is_bots_only(result: &A2SQueryResult) -> bool {
+    result.players > 0 && result.real_players == 0
+}
+
+// Last CS:GO version line; CS2 (same app ID 730) reports 1.39 and later
+const CSGO_LAST_MINOR_VERSION: u32 = 38;
+
+// Derive the engine/game family from the App ID, using the version to tell CS2 from CS:GO
+fn detect_engine(app_id: u16, version: &str) -> &'static str {
+    match app_id {
+        730 => {
+            let mut parts = version.split('.').map(|p| p.parse::<u32>().ok());
+            match (parts.next().flatten(), parts.next().flatten()) {
+                (Some(1), Some(minor)) if minor <= CSGO_LAST_MINOR_VERSION => "csgo",
+                _ => "cs2",
+            }
+        }
+        240 => "css",
+        440 => "tf2",
+        4000 => "gmod",
+        550 => "l4d2",
+        _ => "source",
+    }
+}
+
+// Send one length-prefixed (u32 little-endian) A2S packet over TCP and read the framed reply
+fn tcp_exchange(stream: &mut std::net::TcpStream, packet: &[u8]) -> Result<Vec<u8>, String> {
+    use std::io::{Read, Write};
     
+    let mut frame = (packet.len() as u32).to_le_bytes().to_vec();
+    frame.extend_from_slice(packet);
+    stream.write_all(&frame).map_err(|e| format!("Failed to send query: {}", e))?;
+    
+    let mut len = [0u8; 4];
+    stream.read_exact(&mut len).map_err(|e| format!("Failed to receive: {}", e))?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > A2S_MAX_PACKET_SIZE {
+        return Err(format!("Response frame too large: {} bytes", len));
+    }
+    let mut reply = vec![0u8; len];
+    stream.read_exact(&mut reply).map_err(|e| format!("Failed to receive: {}", e))?;
+    Ok(reply)
+}
+
+// Query A2S_INFO over TCP, for the few query proxies that don't answer over UDP
+// Each packet is framed with a 4-byte length prefix; the challenge flow and parsing match UDP
+fn a2s_query_tcp(addr: SocketAddr, timeout: Duration) -> A2SQueryResult {
+    let mut result = A2SQueryResult::default();
+    
+    let mut stream = match std::net::TcpStream::connect_timeout(&addr, timeout) {
+        Ok(stream) => stream,
+        Err(e) => {
+            result.error = Some(format!("Failed to connect: {}", e));
+            return result;
+        }
+    };
+    if let Err(e) = stream.set_read_timeout(Some(timeout)).and(stream.set_write_timeout(Some(timeout))) {
+        result.error = Some(format!("Failed to set timeout: {}", e));
+        return result;
+    }
+    
+    let mut request = A2S_INFO.to_vec();
+    request.extend_from_slice(&a2s::CHALLENGE_REQUEST.to_le_bytes());
+    let mut sent_at = Instant::now();
+    let mut reply = match tcp_exchange(&mut stream, &request) {
+        Ok(reply) => reply,
+        Err(e) => {
+            result.error = Some(e);
+            return result;
+        }
+    };
+    
+    // Challenge response 'A' - resend with the challenge number
+    if reply.len() >= 9 && reply[..4] == [0xFF, 0xFF, 0xFF, 0xFF] && reply[4] == 0x41 {
+        let mut challenge_request = A2S_INFO.to_vec();
+        challenge_request.extend_from_slice(&reply[5..9]);
+        sent_at = Instant::now();
+        reply = match tcp_exchange(&mut stream, &challenge_request) {
+            Ok(reply) => reply,
+            Err(e) => {
+                result.error = Some(e);
+                return result;
+            }
+        };
+    }
+    result.ping_ms = sent_at.elapsed().as_millis().min(u32::MAX as u128) as u32;
+    
+    if reply.len() < 4 || reply[..4] != [0xFF, 0xFF, 0xFF, 0xFF] {
+        result.error = Some("Invalid response header".to_string());
+        return result;
+    }
+    if let Err(e) = parse_a2s_info(&reply, &mut result) {
+        result.error = Some(e);
+        return result;
+    }
     result.success = true;
     result
 }
 
+// Consecutive queries closer together than this are checked for implausible player count jumps
+const PLAYER_JUMP_WINDOW_SECS: u64 = 120;
+
+// A jump is implausible when it exceeds this share of the slots (and PLAYER_JUMP_MIN_PLAYERS)
+const PLAYER_JUMP_MAX_FRACTION: f64 = 0.5;
+const PLAYER_JUMP_MIN_PLAYERS: i32 = 10;
+
+// Whether the player count moved further since the previous successful query than
+// players joining/leaving can explain (e.g. 3 -> 60 within a minute)
+fn is_implausible_player_jump(result: &A2SQueryResult, history: &server_store::ServerRecord) -> bool {
+    let previous = match history.samples.iter().rev().find(|s| s.online) {
+        Some(sample) => sample,
+        None => return false,
+    };
+    if server_store::unix_now().saturating_sub(previous.timestamp) > PLAYER_JUMP_WINDOW_SECS {
+        return false;
+    }
+    let limit = ((result.max_players as f64 * PLAYER_JUMP_MAX_FRACTION) as i32).max(PLAYER_JUMP_MIN_PLAYERS);
+    (result.players - previous.players).abs() > limit
+}
+
+// Classify server population from player counts
+// Bot-only servers (no real players) are treated as empty
+fn classify_population(result: &A2SQueryResult, thresholds: &settings::PopulationThresholds) -> PopulationState {
+    if result.real_players <= 0 || result.max_players <= 0 {
+        return PopulationState::Empty;
+    }
+    if result.players >= result.max_players {
+        return PopulationState::Full;
+    }
+    
+    let ratio = result.players as f64 / result.max_players as f64;
+    if ratio >= thresholds.near_full_ratio {
+        PopulationState::NearFull
+    } else if ratio >= thresholds.active_ratio {
+        PopulationState::Active
+    } else {
+        PopulationState::Low
+    }
+}
+
+// Error code reported for network work skipped while the network is paused
+const NETWORK_PAUSED: &str = "NetworkPaused";
+
+// Managed state: when set, network activity (auto-refresh, queries, watchers) is skipped,
+// except queries for explicit user actions (background = Some(false))
+#[derive(Default)]
+struct NetworkPause(std::sync::atomic::AtomicBool);
+
+// Whether network activity is currently paused
+fn network_paused(app: &tauri::AppHandle) -> bool {
+    app.state::<NetworkPause>().0.load(std::sync::atomic::Ordering::SeqCst)
+}
+
+// Pause or resume all network activity (explicit user actions still run)
+#[tauri::command]
+async fn set_network_paused(app: tauri::AppHandle, paused: bool) -> Result<(), String> {
+    let previous = app.state::<NetworkPause>().0.swap(paused, std::sync::atomic::Ordering::SeqCst);
+    if previous != paused {
+        log::info!("[Network] Network activity {}", if paused { "paused" } else { "resumed" });
+        let _ = app.emit("network-paused-changed", paused);
+    }
+    Ok(())
+}
+
+// Check whether network activity is paused
+#[tauri::command]
+async fn is_network_paused(app: tauri::AppHandle) -> Result<bool, String> {
+    Ok(network_paused(&app))
+}
+
 // Tauri command for A2S query
 // This allows the frontend to perform direct UDP queries to game servers
+// While the network is paused only explicit user actions (background = false) are sent;
+// background refreshes and calls that don't say are answered with NetworkPaused
+// include_timings = true adds a per-phase timing breakdown for diagnosing slow queries
+// include_raw = true adds the exact request/response bytes (devtools builds only)
+// include_rdns = true adds the reverse DNS hostname of the server (looked up with a short timeout)
+#[tauri::command]
+async fn query_server_a2s(
+    app: tauri::AppHandle,
+    ip: String,
+    port: String,
+    background: Option<bool>,
+    include_timings: Option<bool>,
+    include_raw: Option<bool>,
+    include_rdns: Option<bool>,
+) -> Result<A2SQueryResult, String> {
+    let debug = QueryDebug {
+        timings: include_timings.unwrap_or(false),
+        raw: include_raw.unwrap_or(false),
+        rdns: include_rdns.unwrap_or(false),
+    };
+    query_server(app, ip, port, background, debug, None, None).await
+}
+
+// query_server_a2s, optionally sending from a batch's source port pool
+async fn query_server(
+    app: tauri::AppHandle,
+    ip: String,
+    port: String,
+    background: Option<bool>,
+    debug: QueryDebug,
+    pool: Option<std::sync::Arc<a2s::SocketPool>>,
+    deadline: Option<Instant>,
+) -> Result<A2SQueryResult, String> {
+    let is_favorite = favorites::is_favorite(&app, &ip, &port);
+    if background != Some(false) && network_paused(&app) {
+        return Ok(A2SQueryResult {
+            error: Some(format!("{}: 网络活动已暂停", NETWORK_PAUSED)),
+            error_code: Some(NETWORK_PAUSED.to_string()),
+            is_favorite,
+            ip,
+            port,
+            ..Default::default()
+        });
+    }
+    
+    let app_settings = settings::current(&app);
+    
+    // Per-server overrides take precedence over the global defaults
+    let overrides = server_store::get_record(&app, &server_store::server_key(&ip, &port))
+        .map(|r| r.overrides)
+        .unwrap_or_default();
+    let source_ip = query_source_ip(&ip, &port, &overrides, &app_settings)?;
+    let query_port = overrides.query_port.map(|p| p.to_string()).unwrap_or_else(|| port.clone());
+    let timeout = QueryTimeout {
+        per_reply: overrides.timeout_ms.map(Duration::from_millis).unwrap_or(A2S_DEFAULT_TIMEOUT),
+        deadline,
+    };
+    
+    // Run the blocking DNS lookup and UDP query in a thread pool to avoid blocking the async runtime
+    let app_query = app.clone();
+    let relay_port = query_port.clone();
+    let (mut result, queried_addr) = tokio::task::spawn_blocking(move || {
+        // Hostnames are resolved through the DNS cache so rapid refreshes don't repeat lookups
+        let dns = app_query.state::<dns_cache::DnsCache>();
+        match dns_cache::resolve_all(&dns, &ip) {
+            Ok(addrs) => {
+                // Dual-stack hosts: if the preferred family gets no answer (timeout or no route),
+                // retry over the other one. A source IP can only reach its own family.
+                let candidates: Vec<IpAddr> = addrs
+                    .into_iter()
+                    .filter(|addr| source_ip.map_or(true, |src| src.is_ipv4() == addr.is_ipv4()))
+                    .collect();
+                let mut result = A2SQueryResult {
+                    error: source_ip.map(|src| format!("Source IP {} can't reach any address of {}", src, ip)),
+                    ..Default::default()
+                };
+                let mut queried_addr = None;
+                for addr in candidates {
+                    // No time left to fall back to the other family
+                    if timeout.next_reply().is_none() {
+                        result = deadline_exceeded(result);
+                        break;
+                    }
+                    queried_addr = Some(addr);
+                    let unanswered;
+                    (result, unanswered) = a2s_query(
+                        &addr.to_string(),
+                        &query_port,
+                        source_ip,
+                        timeout,
+                        &app_query.state::<a2s::ChallengeCache>(),
+                        pool.as_deref(),
+                        debug,
+                    );
+                    if result.success {
+                        result.address_family = Some(if addr.is_ipv4() { "IPv4" } else { "IPv6" }.to_string());
+                    }
+                    if !unanswered {
+                        break;
+                    }
+                }
+                // Report the address the caller asked for, not the resolved one
+                result.ip = ip;
+                result.port = port;
+                (result, queried_addr)
+            }
+            Err(e) => (A2SQueryResult {
+                error: Some(e),
+                ip,
+                port,
+                ..Default::default()
+            }, None),
+        }
+    }).await.map_err(|e| format!("Query task failed: {}", e))?;
+    result.query_path = Some("udp".to_string());
+    
+    // Stopped by the batch deadline: nothing was learned about the server, so nothing is recorded
+    if result.error_code.as_deref() == Some(DEADLINE_EXCEEDED) {
+        result.is_favorite = is_favorite;
+        return Ok(result);
+    }
+    
+    // Firewalled networks: ask the backend to run the query when the local one got no answer
+    if !result.success && app_settings.query_relay_enabled {
+        match query_relay::query(&result.ip, &relay_port).await {
+            Ok(mut relayed) => {
+                relayed.ip = result.ip;
+                relayed.port = result.port;
+                relayed.query_path = Some("relay".to_string());
+                // The backend may predate these derived fields, so compute them locally
+                (relayed.name_sanitized, relayed.name_suspect) = check_server_name(&relayed.name);
+                relayed.bots_only = relayed.success && is_bots_only(&relayed);
+                result = relayed;
+            }
+            Err(e) => log::info!("[A2S] Relay fallback failed: {}", e),
+        }
+    }
+    
+    if debug.rdns {
+        if let Some(addr) = queried_addr {
+            result.rdns = dns_cache::reverse_lookup(addr).await;
+        }
+    }
+    
+    result.population_state = classify_population(&result, &app_settings.population_thresholds);
+    if result.success && !result.suspicious_count {
+        let history = server_store::get_record(&app, &server_store::server_key(&result.ip, &result.port));
+        result.suspicious_count = history.is_some_and(|h| is_implausible_player_jump(&result, &h));
+    }
+    server_store::record_query(&app, &result);
+    a2s_cache::store(&app.state::<a2s_cache::A2SCache>(), &result);
+    query_failures::record(&app, &result);
+    
+    result.is_favorite = is_favorite;
+    Ok(result)
+}
+
+// Query a server over TCP instead of UDP (query_path = "tcp")
+// Source IP and per-server overrides don't apply to TCP queries
+#[tauri::command]
+async fn query_server_a2s_tcp(app: tauri::AppHandle, ip: String, port: String) -> Result<A2SQueryResult, String> {
+    let port_num: u16 = port.trim().parse().map_err(|_| format!("Invalid port: {}", port))?;
+    let app_query = app.clone();
+    let mut result = tokio::task::spawn_blocking(move || {
+        let dns = app_query.state::<dns_cache::DnsCache>();
+        let mut result = match dns_cache::resolve(&dns, &ip) {
+            Ok(addr) => a2s_query_tcp(SocketAddr::new(addr, port_num), A2S_DEFAULT_TIMEOUT),
+            Err(e) => A2SQueryResult { error: Some(e), ..Default::default() },
+        };
+        result.ip = ip;
+        result.port = port;
+        result
+    }).await.map_err(|e| format!("Query task failed: {}", e))?;
+    
+    result.query_path = Some("tcp".to_string());
+    result.population_state = classify_population(&result, &settings::current(&app).population_thresholds);
+    query_failures::record(&app, &result);
+    Ok(result)
+}
+
+// Compact per-row badge payload, avoids sending the whole A2SQueryResult for list badges
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ServerBadge {
+    pub online: bool,
+    pub players: i32,
+    pub max_players: i32,
+    pub ping_ms: u32,
+    pub suspicious_count: bool,
+    pub bots_only: bool,
+}
+
+// Get a server's player count badge, served from the A2S cache when fresh
+#[tauri::command]
+async fn server_badge(app: tauri::AppHandle, ip: String, port: String) -> Result<ServerBadge, String> {
+    let cached = a2s_cache::get_fresh(&app.state::<a2s_cache::A2SCache>(), &ip, &port);
+    let result = match cached {
+        Some(result) => result,
+        None => query_server_a2s(app, ip, port, None, None, None, None).await?,
+    };
+    Ok(ServerBadge {
+        online: result.success,
+        players: result.players,
+        max_players: result.max_players,
+        ping_ms: result.ping_ms,
+        suspicious_count: result.suspicious_count,
+        bots_only: result.bots_only,
+    })
+}
+
+// Saved-search filter; unset fields don't constrain the match
+#[derive(serde::Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ServerFilter {
+    // Case-insensitive substring of the map name
+    pub map_contains: Option<String>,
+    pub min_players: Option<i32>,
+    pub max_ping: Option<u32>,
+    pub require_vac: bool,
+    pub no_password: bool,
+}
+
+// Filter match outcome plus the query result it was evaluated against
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct FilterMatch {
+    pub matches: bool,
+    pub result: A2SQueryResult,
+}
+
+// Check a query result against a filter (offline servers never match)
+fn matches_filter(result: &A2SQueryResult, filter: &ServerFilter) -> bool {
+    if !result.success {
+        return false;
+    }
+    if let Some(map) = filter.map_contains.as_deref().map(str::trim).filter(|m| !m.is_empty()) {
+        if !result.map_name.to_lowercase().contains(&map.to_lowercase()) {
+            return false;
+        }
+    }
+    if filter.min_players.is_some_and(|min| result.players < min) {
+        return false;
+    }
+    if filter.max_ping.is_some_and(|max| result.ping_ms > max) {
+        return false;
+    }
+    if filter.require_vac && !result.vac {
+        return false;
+    }
+    if filter.no_password && result.password {
+        return false;
+    }
+    true
+}
+
+// Query a server and report whether it matches a saved-search filter
+#[tauri::command]
+async fn query_matches_filter(app: tauri::AppHandle, ip: String, port: String, filter: ServerFilter) -> Result<FilterMatch, String> {
+    let result = query_server_a2s(app, ip, port, None, None, None, None).await?;
+    Ok(FilterMatch {
+        matches: matches_filter(&result, &filter),
+        result,
+    })
+}
+
+// Default maximum A2S queries in flight at once for batch queries (overridable via set_max_concurrency)
+const BATCH_QUERY_CONCURRENCY: usize = 16;
+
+// Allowed range for the user-configured batch query concurrency
+const MAX_CONCURRENCY_RANGE: std::ops::RangeInclusive<usize> = 1..=64;
+
+// Bounds for the receive buffer of pooled batch sockets (64 KiB to 8 MiB)
+const UDP_RECV_BUFFER_RANGE: std::ops::RangeInclusive<usize> = 64 * 1024..=8 * 1024 * 1024;
+
+// A validated server address
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ServerEntry {
+    pub ip: String,
+    pub port: String,
+}
+
+// An input entry normalize_server_list couldn't use, with the reason
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct RejectedEntry {
+    pub entry: String,
+    pub reason: String,
+}
+
+// Result of normalize_server_list
+#[derive(serde::Serialize, Clone, Debug, Default)]
+pub struct NormalizedServerList {
+    pub servers: Vec<ServerEntry>,
+    pub rejected: Vec<RejectedEntry>,
+}
+
+// Trim, parse "ip:port" strings, validate and dedupe a server list (first occurrence wins)
+fn normalize_entries(entries: Vec<String>) -> NormalizedServerList {
+    let mut list = NormalizedServerList::default();
+    let mut seen = std::collections::HashSet::new();
+    for entry in entries {
+        let trimmed = entry.trim();
+        if trimmed.is_empty() {
+            list.rejected.push(RejectedEntry { entry, reason: "Empty entry".to_string() });
+            continue;
+        }
+        let (ip, port) = match parse_server_address(trimmed) {
+            Some(addr) => addr,
+            None => {
+                list.rejected.push(RejectedEntry { entry, reason: "Invalid address, expected ip:port".to_string() });
+                continue;
+            }
+        };
+        if !seen.insert(format!("{}:{}", ip.to_lowercase(), port)) {
+            list.rejected.push(RejectedEntry { entry, reason: "Duplicate entry".to_string() });
+            continue;
+        }
+        list.servers.push(ServerEntry { ip, port: port.to_string() });
+    }
+    list
+}
+
+// Validate and normalize a batch of "ip:port" server entries before querying
+#[tauri::command]
+async fn normalize_server_list(entries: Vec<String>) -> Result<NormalizedServerList, String> {
+    Ok(normalize_entries(entries))
+}
+
+// Bounds for the batch deadline of query_servers_a2s
+const BATCH_DEADLINE_RANGE_MS: std::ops::RangeInclusive<u64> = 100..=60_000;
+
+// Error code for servers that hadn't answered when the batch deadline passed
+const DEADLINE_EXCEEDED: &str = "DeadlineExceeded";
+
+// Query a batch of "ip:port" entries concurrently
+// Entries are normalized first so malformed or duplicate entries never use a socket
+// source_ports > 0 spreads IPv4 queries over that many bound source ports (at most
+// a2s::MAX_SOURCE_PORTS, which also caps concurrency) to avoid per-source rate limits;
+// the ports are released when the batch finishes
+// deadline_ms bounds the whole batch: servers without an answer by then are returned as
+// DeadlineExceeded and their queries are abandoned, so refresh time doesn't depend on slow servers
+// This is the user-facing refresh, so its results are persisted as the last results
+#[tauri::command]
+async fn query_servers_a2s(
+    app: tauri::AppHandle,
+    entries: Vec<String>,
+    background: Option<bool>,
+    source_ports: Option<usize>,
+    deadline_ms: Option<u64>,
+) -> Result<Vec<A2SQueryResult>, String> {
+    let results = query_batch(app.clone(), entries, background, source_ports, deadline_ms).await?;
+    a2s_cache::save_last_results(&app, &results);
+    Ok(results)
+}
+
+// Batch query behind query_servers_a2s, for internal callers (metrics, map search) whose
+// results must not replace the persisted last results
+async fn query_batch(
+    app: tauri::AppHandle,
+    entries: Vec<String>,
+    background: Option<bool>,
+    source_ports: Option<usize>,
+    deadline_ms: Option<u64>,
+) -> Result<Vec<A2SQueryResult>, String> {
+    if let Some(ms) = deadline_ms {
+        if !BATCH_DEADLINE_RANGE_MS.contains(&ms) {
+            return Err(format!(
+                "deadline_ms must be between {} and {}",
+                BATCH_DEADLINE_RANGE_MS.start(),
+                BATCH_DEADLINE_RANGE_MS.end()
+            ));
+        }
+    }
+    let deadline = deadline_ms.map(|ms| tokio::time::Instant::now() + Duration::from_millis(ms));
+    // Also handed to each query, so queries abandoned at the deadline stop waiting on their sockets
+    let std_deadline = deadline.map(|d| d.into_std());
+    let list = normalize_entries(entries);
+    for rejected in &list.rejected {
+        log::info!("[A2S] Skipping batch entry {:?}: {}", rejected.entry, rejected.reason);
+    }
+    
+    let pool = match source_ports.filter(|&n| n > 0) {
+        Some(count) => {
+            let app_settings = settings::current(&app);
+            let source_ip = parse_source_ip(app_settings.source_ip.as_deref())?;
+            // Clamped in case settings.json was edited by hand
+            let recv_buffer = app_settings
+                .udp_recv_buffer_bytes
+                .map(|n| n.clamp(*UDP_RECV_BUFFER_RANGE.start(), *UDP_RECV_BUFFER_RANGE.end()));
+            Some(std::sync::Arc::new(a2s::SocketPool::bind(count, source_ip, recv_buffer)?))
+        }
+        None => None,
+    };
+
+    // Clamped in case settings.json was edited by hand (0 permits would hang the batch)
+    let concurrency = settings::current(&app)
+        .max_concurrency
+        .map(|n| n.clamp(*MAX_CONCURRENCY_RANGE.start(), *MAX_CONCURRENCY_RANGE.end()))
+        .unwrap_or(BATCH_QUERY_CONCURRENCY);
+    // More queries in flight than pooled ports would only queue up on the sockets
+    let concurrency = pool.as_ref().map_or(concurrency, |p| concurrency.min(p.size()));
+    let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(concurrency));
+    let tasks: Vec<_> = list.servers.into_iter().map(|server| {
+        let app = app.clone();
+        let semaphore = semaphore.clone();
+        let pool = pool.clone();
+        let (ip, port) = (server.ip.clone(), server.port.clone());
+        let task = tauri::async_runtime::spawn(async move {
+            let _permit = semaphore.acquire_owned().await.map_err(|e| e.to_string())?;
+            query_server(app, server.ip, server.port, background, QueryDebug::default(), pool, std_deadline).await
+        });
+        (ip, port, task)
+    }).collect();
+
+    let mut results = Vec::with_capacity(tasks.len());
+    let mut timed_out = 0;
+    for (ip, port, mut task) in tasks {
+        let joined = match deadline {
+            Some(deadline) => tokio::time::timeout_at(deadline, &mut task).await.ok(),
+            None => Some((&mut task).await),
+        };
+        let result = match joined {
+            Some(joined) => joined.map_err(|e| format!("Query task failed: {}", e))??,
+            None => {
+                // A query already on the blocking pool stops at the deadline on its own (see QueryTimeout)
+                task.abort();
+                timed_out += 1;
+                deadline_exceeded(A2SQueryResult { ip, port, ..Default::default() })
+            }
+        };
+        results.push(result);
+    }
+    if timed_out > 0 {
+        log::info!("[A2S] Batch deadline passed with {} of {} servers unanswered", timed_out, results.len());
+    }
+    Ok(results)
+}
+
+// Differences between two servers, returned by compare_servers
+// Numeric differences are b minus a and are None unless both servers answered
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ServerDiff {
+    pub both_online: bool,
+    pub ping_ms: Option<i64>,
+    pub players: Option<i32>,
+    pub real_players: Option<i32>,
+    pub same_map: Option<bool>,
+    // Which side wins: "a", "b" or "equal" (None unless both servers answered)
+    pub lower_ping: Option<&'static str>,
+    pub more_players: Option<&'static str>,
+}
+
+// Both query results of compare_servers plus their differences
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ServerComparison {
+    pub a: A2SQueryResult,
+    pub b: A2SQueryResult,
+    pub diff: ServerDiff,
+}
+
+// "a", "b" or "equal" depending on which value is preferred (higher when higher_wins)
+fn winner<T: PartialOrd>(a: T, b: T, higher_wins: bool) -> &'static str {
+    if a == b {
+        "equal"
+    } else if (a > b) == higher_wins {
+        "a"
+    } else {
+        "b"
+    }
+}
+
+fn diff_servers(a: &A2SQueryResult, b: &A2SQueryResult) -> ServerDiff {
+    let both_online = a.success && b.success;
+    ServerDiff {
+        both_online,
+        ping_ms: both_online.then(|| b.ping_ms as i64 - a.ping_ms as i64),
+        players: both_online.then(|| b.players - a.players),
+        real_players: both_online.then(|| b.real_players - a.real_players),
+        same_map: both_online.then(|| a.map_name == b.map_name),
+        lower_ping: both_online.then(|| winner(a.ping_ms, b.ping_ms, false)),
+        more_players: both_online.then(|| winner(a.real_players, b.real_players, true)),
+    }
+}
+
+// Query two servers concurrently and return both results with a diff for a side-by-side view
+// A server that can't be queried shows up as a failed result; the diff then only has both_online
+#[tauri::command]
+async fn compare_servers(app: tauri::AppHandle, a: ServerEntry, b: ServerEntry) -> Result<ServerComparison, String> {
+    let query = |entry: ServerEntry| {
+        let app = app.clone();
+        async move {
+            let (ip, port) = (entry.ip.clone(), entry.port.clone());
+            query_server(app, entry.ip, entry.port, Some(false), QueryDebug::default(), None, None)
+                .await
+                .unwrap_or_else(|e| A2SQueryResult { error: Some(e), ip, port, ..Default::default() })
+        }
+    };
+    let (a, b) = tokio::join!(query(a), query(b));
+    let diff = diff_servers(&a, &b);
+    Ok(ServerComparison { a, b, diff })
+}
+
+// Error codes returned by query_and_join when the server can't be joined
+const SERVER_OFFLINE: &str = "ServerOffline";
+const SERVER_FULL: &str = "ServerFull";
+const PASSWORD_REQUIRED: &str = "PasswordRequired";
+const SOURCETV_RELAY: &str = "SourceTvRelay";
+
+// Join attempt outcome plus the query result it was based on
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct JoinOutcome {
+    pub joined: bool,
+    // "Code: message" when the join was refused, e.g. "ServerFull: ..."
+    pub error: Option<String>,
+    // Non-blocking caution, e.g. the server doesn't run CS2
+    pub warning: Option<String>,
+    pub result: A2SQueryResult,
+}
+
+// Verify a server is up, not a SourceTV relay, not full and not missing a password,
+// then join it via steam://connect
+#[tauri::command]
+async fn query_and_join(app: tauri::AppHandle, ip: String, port: String, password: Option<String>) -> Result<JoinOutcome, String> {
+    // Built first so a malformed address is rejected before anything is queried
+    let url = steam::connect_url(&ip, &port, password.as_deref())?;
+    let result = query_server_a2s(app.clone(), ip.clone(), port.clone(), Some(false), None, None, None).await?;
+    let has_password = password.as_deref().is_some_and(|p| !p.is_empty());
+
+    let refused = if !result.success {
+        Some(format!("{}: 服务器离线或无响应", SERVER_OFFLINE))
+    } else if result.is_sourcetv {
+        Some(format!("{}: 该服务器是 SourceTV 转播，无法加入游戏", SOURCETV_RELAY))
+    } else if result.max_players > 0 && result.players >= result.max_players {
+        Some(format!("{}: 服务器已满", SERVER_FULL))
+    } else if result.password && !has_password {
+        Some(format!("{}: 该服务器需要密码", PASSWORD_REQUIRED))
+    } else {
+        None
+    };
+    if refused.is_some() {
+        return Ok(JoinOutcome { joined: false, error: refused, warning: None, result });
+    }
+    
+    // Not refused: the game may still fail to connect, so just let the UI know
+    let warning = (result.engine != "cs2")
+        .then(|| format!("该服务器可能不是 CS2 服务器 ({})", result.engine));
+
+    let app_launch = app.clone();
+    let launched = tokio::task::spawn_blocking(move || steam::open_connect_url(&app_launch, &url))
+        .await
+        .map_err(|e| format!("Launch task failed: {}", e))?;
+
+    Ok(JoinOutcome {
+        joined: launched.is_ok(),
+        error: launched.err(),
+        warning,
+        result,
+    })
+}
+
+// Set the fill ratios used to classify server population
+// active_ratio must be below near_full_ratio, both within (0, 1]
 #[tauri::command]
-async fn query_server_a2s(ip: String, port: String) -> Result<A2SQueryResult, String> {
-    // Run the blocking UDP query in a thread pool to avoid blocking the async runtime
-    let result = tokio::task::spawn_blocking(move || {
-        a2s_query(&ip, &port)
+async fn set_population_thresholds(app: tauri::AppHandle, active_ratio: f64, near_full_ratio: f64) -> Result<(), String> {
+    if !(active_ratio > 0.0 && active_ratio < near_full_ratio && near_full_ratio <= 1.0) {
+        return Err("Thresholds must satisfy 0 < active_ratio < near_full_ratio <= 1".to_string());
+    }
+    settings::update(&app, |s| {
+        s.population_thresholds = settings::PopulationThresholds { active_ratio, near_full_ratio };
+    })?;
+    Ok(())
+}
+
+// Enable or disable falling back to the backend relay when local UDP queries fail
+#[tauri::command]
+async fn set_query_relay(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    settings::update(&app, |s| s.query_relay_enabled = enabled)?;
+    Ok(())
+}
+
+// Limit how many A2S queries a batch runs at once (1-64, default 16)
+// Lower values ease load on weak CPUs/networks but make refreshing large lists slower;
+// takes effect from the next batch
+#[tauri::command]
+async fn set_max_concurrency(app: tauri::AppHandle, n: usize) -> Result<(), String> {
+    if !MAX_CONCURRENCY_RANGE.contains(&n) {
+        return Err(format!(
+            "max_concurrency must be between {} and {}",
+            MAX_CONCURRENCY_RANGE.start(),
+            MAX_CONCURRENCY_RANGE.end()
+        ));
+    }
+    settings::update(&app, |s| s.max_concurrency = Some(n))?;
+    Ok(())
+}
+
+// Set the receive buffer (SO_RCVBUF) of the pooled sockets used by batch queries with
+// source_ports, or None for the OS default. A larger buffer keeps reply bursts from being
+// dropped when scanning hundreds of servers; takes effect from the next batch
+#[tauri::command]
+async fn set_udp_recv_buffer(app: tauri::AppHandle, bytes: Option<usize>) -> Result<(), String> {
+    if let Some(bytes) = bytes {
+        if !UDP_RECV_BUFFER_RANGE.contains(&bytes) {
+            return Err(format!(
+                "udp_recv_buffer_bytes must be between {} and {}",
+                UDP_RECV_BUFFER_RANGE.start(),
+                UDP_RECV_BUFFER_RANGE.end()
+            ));
+        }
+    }
+    settings::update(&app, |s| s.udp_recv_buffer_bytes = bytes)?;
+    Ok(())
+}
+
+// Server summary: A2S_INFO result plus details derived from A2S_RULES
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ServerSummary {
+    #[serde(flatten)]
+    pub info: A2SQueryResult,
+    // Advertised tickrate, None when the server doesn't advertise it or rules are unavailable
+    pub tickrate: Option<u32>,
+}
+
+// Query a server's info and rules and return a combined summary
+#[tauri::command]
+async fn server_summary(app: tauri::AppHandle, ip: String, port: String) -> Result<ServerSummary, String> {
+    let info = query_server_a2s(app.clone(), ip.clone(), port.clone(), None, None, None, None).await?;
+    if !info.success {
+        return Ok(ServerSummary { info, tickrate: None });
+    }
+    
+    let source_ip = server_source_ip(&app, &ip, &port)?;
+    let rules = tokio::task::spawn_blocking(move || {
+        let dns = app.state::<dns_cache::DnsCache>();
+        let addr = dns_cache::resolve(&dns, &ip)?;
+        a2s::a2s_rules(&addr.to_string(), &port, source_ip, &app.state::<a2s::ChallengeCache>())
     }).await.map_err(|e| format!("Query task failed: {}", e))?;
     
+    let tickrate = match rules {
+        Ok(rules) => a2s::detect_tickrate(&rules),
+        Err(e) => {
+            log::info!("[A2S] Rules unavailable for summary: {}", e);
+            None
+        }
+    };
+    
+    Ok(ServerSummary { info, tickrate })
+}
+
+// Server rule (convar) admins set to the token the backend issued, to prove they run the server
+const OWNERSHIP_RULE_KEY: &str = "upkk_verify";
+const OWNERSHIP_TOKEN_MISSING: &str = "OwnershipTokenMissing";
+
+// Result of verify_server_ownership
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct OwnershipVerification {
+    pub verified: bool,
+    // Rule that was checked
+    pub rule_key: String,
+}
+
+// Compare two tokens (HMAC-SHA256 digests in hex) without an early exit on the first differing byte
+fn ownership_token_matches(expected: &[u8], advertised: &str) -> bool {
+    match hex::decode(advertised.trim()) {
+        Ok(advertised) if advertised.len() == expected.len() => {
+            expected.iter().zip(&advertised).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+        }
+        _ => false,
+    }
+}
+
+// Check that a server advertises the ownership token the backend issued (an HMAC-SHA256 digest
+// in hex) in its upkk_verify rule. Errors with OwnershipTokenMissing when the rule isn't set.
+#[tauri::command]
+async fn verify_server_ownership(
+    app: tauri::AppHandle,
+    ip: String,
+    port: String,
+    expected_token: String,
+) -> Result<OwnershipVerification, String> {
+    let expected = hex::decode(expected_token.trim())
+        .ok()
+        .filter(|token| token.len() == 32)
+        .ok_or_else(|| "验证令牌格式无效".to_string())?;
+    
+    let source_ip = server_source_ip(&app, &ip, &port)?;
+    let rules = tokio::task::spawn_blocking(move || {
+        let dns = app.state::<dns_cache::DnsCache>();
+        let addr = dns_cache::resolve(&dns, &ip)?;
+        a2s::a2s_rules(&addr.to_string(), &port, source_ip, &app.state::<a2s::ChallengeCache>())
+    }).await.map_err(|e| format!("Query task failed: {}", e))??;
+    
+    let advertised = rules.get(OWNERSHIP_RULE_KEY).ok_or_else(|| {
+        format!("{}: 服务器未设置 {} 规则", OWNERSHIP_TOKEN_MISSING, OWNERSHIP_RULE_KEY)
+    })?;
+    Ok(OwnershipVerification {
+        verified: ownership_token_matches(&expected, advertised),
+        rule_key: OWNERSHIP_RULE_KEY.to_string(),
+    })
+}
+
+// Bounds for stability_score: number of queries and the minimum spacing between them
+const STABILITY_SAMPLES_RANGE: std::ops::RangeInclusive<u32> = 2..=20;
+const STABILITY_MIN_INTERVAL_MS: u64 = 500;
+
+// Reliability estimate from repeated queries, returned by stability_score
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct StabilityReport {
+    // 0-100, weighted from response rate (60), ping consistency (25) and player count consistency (15)
+    pub score: u32,
+    pub response_rate: f64,
+    pub ping_stddev_ms: Option<f64>,
+    pub samples: Vec<server_store::QuerySample>,
+}
+
+// Mean and standard deviation of a set of values
+fn mean_stddev(values: &[f64]) -> Option<(f64, f64)> {
+    if values.is_empty() {
+        return None;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    Some((mean, variance.sqrt()))
+}
+
+// Query a server `samples` times, interval_ms apart, and score how reliable it is
+// Consistency parts need at least two responses and count as 0 otherwise
+#[tauri::command]
+async fn stability_score(
+    app: tauri::AppHandle,
+    ip: String,
+    port: String,
+    samples: u32,
+    interval_ms: u64,
+) -> Result<StabilityReport, String> {
+    if !STABILITY_SAMPLES_RANGE.contains(&samples) {
+        return Err(format!(
+            "samples must be between {} and {}",
+            STABILITY_SAMPLES_RANGE.start(),
+            STABILITY_SAMPLES_RANGE.end()
+        ));
+    }
+    if interval_ms < STABILITY_MIN_INTERVAL_MS {
+        return Err(format!("interval_ms must be at least {}", STABILITY_MIN_INTERVAL_MS));
+    }
+    
+    let mut collected = Vec::with_capacity(samples as usize);
+    let mut max_players = 0;
+    for i in 0..samples {
+        if i > 0 {
+            tokio::time::sleep(Duration::from_millis(interval_ms)).await;
+        }
+        let result = query_server_a2s(app.clone(), ip.clone(), port.clone(), Some(false), None, None, None).await?;
+        max_players = max_players.max(result.max_players);
+        collected.push(server_store::QuerySample {
+            timestamp: server_store::unix_now(),
+            online: result.success,
+            players: result.players,
+            ping_ms: result.success.then_some(result.ping_ms),
+        });
+    }
+    
+    let online: Vec<_> = collected.iter().filter(|s| s.online).collect();
+    let response_rate = online.len() as f64 / collected.len() as f64;
+    let pings: Vec<f64> = online.iter().filter_map(|s| s.ping_ms).map(|p| p as f64).collect();
+    let players: Vec<f64> = online.iter().map(|s| s.players as f64).collect();
+    
+    let ping_stats = mean_stddev(&pings);
+    let (ping_consistency, player_consistency) = if online.len() >= 2 {
+        // Coefficient of variation: jitter relative to the typical ping
+        let ping = ping_stats
+            .map(|(mean, stddev)| 1.0 - (stddev / mean.max(1.0)).min(1.0))
+            .unwrap_or(0.0);
+        // Player spread relative to the slot count
+        let player = mean_stddev(&players)
+            .map(|(_, stddev)| 1.0 - (stddev * 4.0 / max_players.max(1) as f64).min(1.0))
+            .unwrap_or(0.0);
+        (ping, player)
+    } else {
+        (0.0, 0.0)
+    };
+    
+    let score = response_rate * 60.0 + ping_consistency * 25.0 + player_consistency * 15.0;
+    Ok(StabilityReport {
+        score: score.round().clamp(0.0, 100.0) as u32,
+        response_rate,
+        ping_stddev_ms: ping_stats.map(|(_, stddev)| stddev),
+        samples: collected,
+    })
+}
+
+// Round trips of the two legs of the A2S_INFO challenge handshake (see probe_server)
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct LatencyProbe {
+    // Bare request answered by the small S2C_CHALLENGE packet; None when the server answered
+    // the first request directly without issuing a challenge
+    pub challenge_rtt_ms: Option<f64>,
+    // Challenged request answered by the full A2S_INFO reply
+    pub info_rtt_ms: f64,
+}
+
+// Send one A2S_INFO request on a connected socket and time its reply
+fn timed_info_round_trip(socket: &UdpSocket, challenge: u32, buf: &mut [u8]) -> Result<(ChallengeReply, f64), String> {
+    let sent_at = Instant::now();
+    a2s::send_with_challenge_to(socket, a2s::Peer::Connected, &A2S_INFO, challenge)?;
+    let reply = a2s::recv_reply_from(socket, a2s::Peer::Connected, buf, &mut 0)
+        .map_err(|e| format!("Failed to receive: {}", e))?;
+    Ok((reply, elapsed_ms(sent_at)))
+}
+
+// Time both legs of the challenge handshake separately. Both requests are about the same size,
+// but the challenge reply is a few bytes while the info reply is a few hundred, so the gap
+// between the two round trips hints at how much of the latency sits on the receive path.
+// This is an estimate: both legs are full round trips (server processing included), and
+// true one-way latency can't be measured without synchronized clocks on both ends.
+#[tauri::command]
+async fn probe_server(app: tauri::AppHandle, ip: String, port: String) -> Result<LatencyProbe, String> {
+    let source_ip = server_source_ip(&app, &ip, &port)?;
+    tokio::task::spawn_blocking(move || {
+        let dns = app.state::<dns_cache::DnsCache>();
+        let addr = dns_cache::resolve(&dns, &ip)?.to_string();
+        let socket = a2s::bind_socket(&addr, source_ip, A2S_DEFAULT_TIMEOUT)?;
+        a2s::connect_to(&socket, &addr, &port)?;
+        
+        // Always start without a cached challenge so the handshake actually happens
+        let mut buf = vec![0u8; A2S_MAX_PACKET_SIZE];
+        match timed_info_round_trip(&socket, a2s::CHALLENGE_REQUEST, &mut buf)? {
+            (ChallengeReply::Response(_), info_rtt_ms) => Ok(LatencyProbe { challenge_rtt_ms: None, info_rtt_ms }),
+            (ChallengeReply::Challenge(challenge), challenge_rtt_ms) => {
+                a2s::store_challenge(&app.state::<a2s::ChallengeCache>(), &addr, &port, &A2S_INFO, challenge);
+                match timed_info_round_trip(&socket, challenge, &mut buf)? {
+                    (ChallengeReply::Response(_), info_rtt_ms) => Ok(LatencyProbe {
+                        challenge_rtt_ms: Some(challenge_rtt_ms),
+                        info_rtt_ms,
+                    }),
+                    (ChallengeReply::Challenge(_), _) => Err("Server rejected the challenge response".to_string()),
+                }
+            }
+        }
+    }).await.map_err(|e| format!("Query task failed: {}", e))?
+}
+
+// Query a server's info plus its player list to count human players precisely
+// Falls back to the plain info result (human_players = None) when the player list is unavailable
+#[tauri::command]
+async fn query_human_players(app: tauri::AppHandle, ip: String, port: String) -> Result<A2SQueryResult, String> {
+    let mut result = query_server_a2s(app.clone(), ip.clone(), port.clone(), None, None, None, None).await?;
+    if !result.success {
+        return Ok(result);
+    }
+    
+    let source_ip = server_source_ip(&app, &ip, &port)?;
+    let players = tokio::task::spawn_blocking(move || {
+        let dns = app.state::<dns_cache::DnsCache>();
+        let addr = dns_cache::resolve(&dns, &ip)?;
+        a2s::a2s_players(&addr.to_string(), &port, source_ip, &app.state::<a2s::ChallengeCache>())
+    }).await.map_err(|e| format!("Query task failed: {}", e))?;
+    
+    match players {
+        Ok(players) => result.human_players = Some(a2s::count_humans(&players)),
+        Err(e) => log::info!("[A2S] Player list unavailable: {}", e),
+    }
     Ok(result)
 }
 
+// Parse an optional source IP setting, treating empty strings as unset
+fn parse_source_ip(source_ip: Option<&str>) -> Result<Option<IpAddr>, String> {
+    match source_ip.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(s) => s
+            .parse::<IpAddr>()
+            .map(Some)
+            .map_err(|_| format!("Invalid source IP address: {}", s)),
+        None => Ok(None),
+    }
+}
+
+// Addresses that can't belong to a server elsewhere: private, loopback, link-local and the like
+// (IPv4-mapped IPv6 addresses are checked as IPv4)
+fn is_local_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_documentation()
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_local_address(IpAddr::V4(v4));
+            }
+            // fc00::/7 unique local, fe80::/10 link-local
+            let first = v6.segments()[0];
+            v6.is_loopback() || v6.is_unspecified() || first & 0xfe00 == 0xfc00 || first & 0xffc0 == 0xfe80
+        }
+    }
+}
+
+// Local address queries to ip:port are sent from: the server's source_ip override (e.g. the
+// VPN interface on a split tunnel), else the global setting, else the OS default route
+fn query_source_ip(
+    ip: &str,
+    port: &str,
+    overrides: &server_store::ServerOverrides,
+    app_settings: &settings::AppSettings,
+) -> Result<Option<IpAddr>, String> {
+    let server_override = overrides.source_ip.as_deref().filter(|s| !s.trim().is_empty());
+    let source_ip = parse_source_ip(server_override.or(app_settings.source_ip.as_deref()))?;
+    match (source_ip, server_override.is_some()) {
+        (Some(addr), true) => log::info!("[A2S] Querying {}:{} from {} (server override)", ip, port, addr),
+        (Some(addr), false) => log::debug!("[A2S] Querying {}:{} from {}", ip, port, addr),
+        (None, _) => log::debug!("[A2S] Querying {}:{} from the default route", ip, port),
+    }
+    Ok(source_ip)
+}
+
+// query_source_ip for queries that don't otherwise need the server's overrides
+fn server_source_ip(app: &tauri::AppHandle, ip: &str, port: &str) -> Result<Option<IpAddr>, String> {
+    let overrides = server_store::get_record(app, &server_store::server_key(ip, port))
+        .map(|r| r.overrides)
+        .unwrap_or_default();
+    query_source_ip(ip, port, &overrides, &settings::current(app))
+}
+
+// Set the local IP address A2S queries are sent from (None to use the OS default route)
+// Useful on multi-homed machines (VPN + LAN) where the OS picks the wrong interface
+#[tauri::command]
+async fn set_query_source_ip(app: tauri::AppHandle, source_ip: Option<String>) -> Result<(), String> {
+    let addr = parse_source_ip(source_ip.as_deref())?;
+    
+    // Make sure the address belongs to a local interface before saving it
+    if let Some(addr) = addr {
+        UdpSocket::bind(SocketAddr::new(addr, 0))
+            .map_err(|e| format!("Failed to bind to source IP {}: {}", addr, e))?;
+    }
+    
+    settings::update(&app, |s| s.source_ip = addr.map(|a| a.to_string()))?;
+    Ok(())
+}
+
 // Escape a string for safe JavaScript embedding
 fn escape_js_string(s: &str) -> String {
     s.replace('\\', "\\\\")
@@ -329,8 +1774,262 @@ fn generate_post_form_js(url: &str, uid: &str, auth: &str) -> String {
 }
 
 #[tauri::command]
-async fn open_forum_window(app: tauri::AppHandle) -> Result<(), String> {
-    open_url_in_browser_window(app, "forum".to_string(), FORUM_URL.to_string(), "Upkk 社区论坛".to_string()).await
+async fn open_forum_window(app: tauri::AppHandle) -> Result<(), String> {
+    // Restore the previous session's tabs into a newly opened window
+    if app.get_webview_window("forum").is_none() {
+        forum_session::queue_restore(&app);
+    }
+    open_url_in_browser_window(app, "forum".to_string(), FORUM_URL.to_string(), "Upkk 社区论坛".to_string()).await
+}
+
+// Hosts the forum/browser windows may navigate to
+const ALLOWED_NAVIGATION_HOSTS: [&str; 2] = ["bbs.upkk.com", "servers.upkk.com"];
+
+// Error code returned when a navigation target isn't on the allowlist
+const NAVIGATION_BLOCKED: &str = "NavigationBlocked";
+
+// Check a URL against the navigation allowlist (about: pages and upkk domains)
+// Used by on_navigation and to pre-check forum_navigate targets
+fn is_allowed_navigation(url: &Url) -> bool {
+    match url.scheme() {
+        "about" => true,
+        "http" | "https" => url
+            .host_str()
+            .is_some_and(|host| ALLOWED_NAVIGATION_HOSTS.contains(&host)),
+        _ => false,
+    }
+}
+
+// Parse a user-supplied URL, defaulting scheme-less input to https
+fn normalize_url(input: &str) -> Result<Url, String> {
+    let trimmed = input.trim();
+    if trimmed.contains("://") || trimmed.starts_with("about:") {
+        trimmed.parse().map_err(|e: url::ParseError| e.to_string())
+    } else {
+        format!("https://{}", trimmed).parse().map_err(|e: url::ParseError| e.to_string())
+    }
+}
+
+// Forum session status returned by forum_check_session
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ForumSessionStatus {
+    pub logged_in: bool,
+}
+
+// Check whether a URL is the forum's login page (Discuz member.php?mod=logging&action=login)
+fn is_forum_login_page(url: &Url) -> bool {
+    url.host_str() == Some("bbs.upkk.com")
+        && url.path().ends_with("member.php")
+        && url.query_pairs().any(|(k, v)| k == "mod" && v == "logging")
+        && url.query_pairs().any(|(k, v)| k == "action" && v == "login")
+}
+
+// Emit forum-session-expired when a navigation lands on the forum login page
+// The frontend listens for this event to trigger re-authentication
+fn check_forum_session_expired(app: &tauri::AppHandle, url: &Url) {
+    if is_forum_login_page(url) {
+        println!("[Forum] Navigation landed on login page, session expired: {}", url);
+        let _ = app.emit("forum-session-expired", url.to_string());
+    }
+}
+
+// Site that hosts shareable server invite links (https://servers.upkk.com/?connect=ip:port)
+const SERVER_INVITE_BASE_URL: &str = "https://servers.upkk.com/";
+
+// Build a shareable invite link for posting on the forum
+#[tauri::command]
+async fn make_forum_server_link(ip: String, port: String, name: Option<String>) -> Result<String, String> {
+    let connect = format!("{}:{}", ip.trim(), port.trim());
+    let mut params = vec![("connect", connect)];
+    if let Some(name) = name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()) {
+        params.push(("name", name));
+    }
+    let url = Url::parse_with_params(SERVER_INVITE_BASE_URL, &params).map_err(|e| e.to_string())?;
+    Ok(url.to_string())
+}
+
+// Server address carried by a join-request event
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct JoinRequest {
+    pub ip: String,
+    pub port: String,
+    pub name: Option<String>,
+}
+
+// Parse and validate an "ip:port" / "host:port" / "[v6]:port" address
+fn parse_server_address(address: &str) -> Option<(String, u16)> {
+    let (host, port) = address.trim().rsplit_once(':')?;
+    let port = port.parse::<u16>().ok().filter(|p| *p != 0)?;
+    let host = host.strip_prefix('[').and_then(|h| h.strip_suffix(']')).unwrap_or(host);
+    let valid_host = host.parse::<IpAddr>().is_ok()
+        || (!host.is_empty()
+            && host.len() <= 253
+            && host.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-'));
+    if valid_host { Some((host.to_string(), port)) } else { None }
+}
+
+// Intercept clicks on server invite links and emit join-request instead of navigating
+// Returns true if the navigation was an invite link (it must then be cancelled);
+// malformed connect params are ignored and the page loads normally
+fn handle_server_invite_link(app: &tauri::AppHandle, url: &Url) -> bool {
+    if url.host_str() != Some("servers.upkk.com") {
+        return false;
+    }
+    let params: std::collections::HashMap<String, String> = url.query_pairs()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    let connect = match params.get("connect") {
+        Some(c) => c,
+        None => return false,
+    };
+    let (ip, port) = match parse_server_address(connect) {
+        Some(addr) => addr,
+        None => {
+            println!("[Forum] Ignoring malformed connect param: {}", connect);
+            return false;
+        }
+    };
+    
+    println!("[Forum] Server invite link intercepted: {}:{}", ip, port);
+    let _ = app.emit("join-request", JoinRequest {
+        ip,
+        port: port.to_string(),
+        name: params.get("name").filter(|n| !n.is_empty()).cloned(),
+    });
+    true
+}
+
+#[tauri::command]
+async fn forum_check_session(app: tauri::AppHandle) -> Result<ForumSessionStatus, String> {
+    if let Some(window) = app.get_webview_window("forum") {
+        let forum_url: Url = FORUM_URL.parse().map_err(|e: url::ParseError| e.to_string())?;
+        let cookies = window.cookies_for_url(forum_url).map_err(|e| e.to_string())?;
+        // Discuz keeps the login token in a "<cookiepre>_auth" cookie
+        let logged_in = cookies
+            .iter()
+            .any(|c| c.name().ends_with("_auth") && !c.value().is_empty());
+        Ok(ForumSessionStatus { logged_in })
+    } else {
+        Err("论坛窗口未打开".to_string())
+    }
+}
+
+// Discuz exposes the visitor's uid as discuz_uid ("0" for guests) and shows the username in the top bar
+const FORUM_LOGIN_PROBE_EXPR: &str = "{ uid: String(window.discuz_uid || '0'), \
+    username: ((document.querySelector('.vwmy a') || {}).textContent || '').trim() }";
+
+// Managed flag: the forum window was just opened and its first page load should be probed
+#[derive(Default)]
+struct ForumLoginProbe(std::sync::atomic::AtomicBool);
+
+// Probe result read from the forum page
+#[derive(serde::Deserialize)]
+struct ForumLoginProbeData {
+    uid: String,
+    username: String,
+}
+
+// Payload of the forum-login-state event
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ForumLoginState {
+    pub logged_in: bool,
+    pub username: Option<String>,
+    // Credentials are saved, so the UI can re-authenticate without asking
+    pub has_saved_credentials: bool,
+}
+
+// Whether the forum window's next page load runs the login probe
+fn forum_login_probe_pending(window: &tauri::WebviewWindow) -> bool {
+    window.app_handle().state::<ForumLoginProbe>().0.load(std::sync::atomic::Ordering::SeqCst)
+}
+
+// After the first page of a newly opened forum window loads, read whether the session carried
+// over and emit forum-login-state. When logged out with saved credentials and forum_auto_reauth
+// is on, forum-session-expired is emitted too so the frontend re-authenticates right away.
+fn detect_forum_login_state(window: &tauri::WebviewWindow) {
+    use std::sync::atomic::Ordering;
+    
+    // Other browser windows share the page-load hook but must not consume the flag
+    if window.label() != "forum" {
+        return;
+    }
+    if !window.app_handle().state::<ForumLoginProbe>().0.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let window = window.clone();
+    tauri::async_runtime::spawn(async move {
+        let app = window.app_handle().clone();
+        let probe = match forum_bridge::eval_with_result(&app, &window, FORUM_LOGIN_PROBE_EXPR).await {
+            Ok(data) => serde_json::from_str::<ForumLoginProbeData>(&data).map_err(|e| e.to_string()),
+            Err(e) => Err(e),
+        };
+        // The bridge reply is a (cancelled) navigation that would also cancel a tab switch started
+        // before it, so the tabs inject_page_scripts held back are only opened now
+        if !settings::current(&app).tab_bar_disabled {
+            forum_session::apply_pending(&window);
+        }
+        let probe = match probe {
+            Ok(probe) => probe,
+            Err(e) => {
+                log::warn!("[Forum] Failed to read login state: {}", e);
+                return;
+            }
+        };
+        let logged_in = probe.uid != "0" && !probe.uid.is_empty();
+        let state = ForumLoginState {
+            logged_in,
+            username: Some(probe.username).filter(|name| logged_in && !name.is_empty()),
+            has_saved_credentials: secure_storage::has_saved_credentials(&app),
+        };
+        log::info!("[Forum] Login state on open: logged_in={}", state.logged_in);
+        let _ = app.emit("forum-login-state", state.clone());
+        
+        if !state.logged_in && state.has_saved_credentials && settings::current(&app).forum_auto_reauth {
+            if let Ok(url) = window.url() {
+                let _ = app.emit("forum-session-expired", url.to_string());
+            }
+        }
+    });
+}
+
+// Re-authenticate the forum automatically when it opens logged out and credentials are saved
+#[tauri::command]
+async fn set_forum_auto_reauth(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    settings::update(&app, |s| s.forum_auto_reauth = enabled)?;
+    Ok(())
+}
+
+// One tab of the injected tab manager, as reported by forum_get_tabs
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct ForumTab {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub active: bool,
+}
+
+// Serialize the injected tab manager's tabs (null if it hasn't initialized yet)
+const FORUM_GET_TABS_EXPR: &str = "window.__xprojTabs ? window.__xprojTabs.getTabs().map(function(t) { \
+    return { id: t.id, url: t.url, title: t.title, active: t.id === window.__xprojTabs.getActiveTabId() }; \
+}) : null";
+
+// Read the open tabs from a window's tab manager (empty if it isn't initialized yet)
+async fn read_forum_tabs(app: &tauri::AppHandle, window: &tauri::WebviewWindow) -> Result<Vec<ForumTab>, String> {
+    let data = forum_bridge::eval_with_result(app, window, FORUM_GET_TABS_EXPR).await?;
+    let tabs: Option<Vec<ForumTab>> = serde_json::from_str(&data)
+        .map_err(|e| format!("Failed to parse tabs: {}", e))?;
+    Ok(tabs.unwrap_or_default())
+}
+
+// Read the open tabs from the forum window's tab manager
+// Returns an empty list if the tab manager isn't initialized yet
+#[tauri::command]
+async fn forum_get_tabs(app: tauri::AppHandle) -> Result<Vec<ForumTab>, String> {
+    if let Some(window) = app.get_webview_window("forum") {
+        read_forum_tabs(&app, &window).await
+    } else {
+        Err("论坛窗口未打开".to_string())
+    }
 }
 
 // JavaScript to create a browser-like multi-tab interface for the forum
@@ -339,8 +2038,8 @@ const FORUM_TAB_MANAGER_JS: &str = r#"
 (function() {
     console.log('[XProj] Tab manager script starting, location:', window.location.href);
     
-    // Prevent double initialization
-    if (window.__xprojTabManager) {
+    // Prevent double initialization (re-initialize if an in-page navigation removed the bar)
+    if (window.__xprojTabManager && document.getElementById('xproj-tab-bar')) {
         console.log('[XProj] Already initialized, skipping');
         return;
     }
@@ -367,6 +2066,15 @@ const FORUM_TAB_MANAGER_JS: &str = r#"
         var tabIdCounter = 0;
         var tabHistory = {}; // Store history for each tab
         
+        // Push onto a back/forward list, dropping the oldest entries beyond the limit set from Rust
+        function pushHistory(list, url) {
+            list.push(url);
+            var limit = window.__xprojTabHistoryLimit || 50;
+            if (list.length > limit) {
+                list.splice(0, list.length - limit);
+            }
+        }
+        
         // Inject CSS styles for the tab bar
         var style = document.createElement('style');
         style.textContent = `
@@ -588,7 +2296,7 @@ const FORUM_TAB_MANAGER_JS: &str = r#"
                 // Update the tab's URL and title
                 if (activeTab.url !== window.location.href) {
                     // Add to back history
-                    tabHistory[activeTabId].back.push(activeTab.url);
+                    pushHistory(tabHistory[activeTabId].back, activeTab.url);
                     tabHistory[activeTabId].forward = [];
                 }
                 activeTab.url = window.location.href;
@@ -666,7 +2374,7 @@ const FORUM_TAB_MANAGER_JS: &str = r#"
     backBtn.onclick = function() {
         var history = tabHistory[activeTabId];
         if (history && history.back.length > 0) {
-            history.forward.push(window.location.href);
+            pushHistory(history.forward, window.location.href);
             var url = history.back.pop();
             var tab = tabs.find(function(t) { return t.id === activeTabId; });
             if (tab) tab.url = url;
@@ -677,7 +2385,7 @@ const FORUM_TAB_MANAGER_JS: &str = r#"
     forwardBtn.onclick = function() {
         var history = tabHistory[activeTabId];
         if (history && history.forward.length > 0) {
-            history.back.push(window.location.href);
+            pushHistory(history.back, window.location.href);
             var url = history.forward.pop();
             var tab = tabs.find(function(t) { return t.id === activeTabId; });
             if (tab) tab.url = url;
@@ -770,6 +2478,100 @@ const FORUM_TAB_MANAGER_JS: &str = r#"
 })();
 "#;
 
+// JavaScript injected after the tab bar to push a site fixed/sticky header below it,
+// otherwise the 40px tab bar and the forum's own header overlap
+const FORUM_HEADER_OFFSET_JS: &str = r#"
+(function() {
+    if (!document.body || !document.getElementById('xproj-tab-bar')) {
+        return;
+    }
+    var candidates = document.querySelectorAll('body > *, header, #hd, #toptb, .header');
+    for (var i = 0; i < candidates.length; i++) {
+        var el = candidates[i];
+        if (el.id === 'xproj-tab-bar' || el.dataset.xprojOffset) {
+            continue;
+        }
+        var style = window.getComputedStyle(el);
+        if (style.position !== 'fixed' && style.position !== 'sticky') {
+            continue;
+        }
+        var rect = el.getBoundingClientRect();
+        // Only full-width bars pinned to the top of the viewport count as a header
+        if (rect.top < 40 && rect.height > 0 && rect.width >= window.innerWidth * 0.5) {
+            el.style.setProperty('top', (parseFloat(style.top) || 0) + 40 + 'px', 'important');
+            el.dataset.xprojOffset = '1';
+            console.log('[XProj] Offset fixed header:', el.tagName, el.id || el.className);
+        }
+    }
+})();
+"#;
+
+// History API hook reporting client-side navigation (pushState/replaceState/popstate) to Rust,
+// which re-applies the tab bar and theme since no page load event fires
+const FORUM_SPA_HOOK_JS: &str = r#"
+(function() {
+    if (window.__xprojSpaHook) {
+        return;
+    }
+    window.__xprojSpaHook = true;
+    var lastUrl = window.location.href;
+    function notify() {
+        if (window.location.href === lastUrl) {
+            return;
+        }
+        lastUrl = window.location.href;
+        window.location.href = 'xproj://forum/spa-navigate?url=' + encodeURIComponent(lastUrl);
+    }
+    ['pushState', 'replaceState'].forEach(function(name) {
+        var original = history[name];
+        history[name] = function() {
+            var result = original.apply(this, arguments);
+            setTimeout(notify, 0);
+            return result;
+        };
+    });
+    window.addEventListener('popstate', function() { setTimeout(notify, 0); });
+})();
+"#;
+
+// Default depth of each tab's back/forward history in the injected tab manager
+const DEFAULT_TAB_HISTORY_LIMIT: usize = 50;
+
+// Largest tab history depth set_tab_history_limit accepts
+const MAX_TAB_HISTORY_LIMIT: usize = 1000;
+
+// Script handing the configured history depth to the tab manager
+fn tab_history_limit_js(app: &tauri::AppHandle) -> String {
+    let limit = settings::current(app).tab_history_limit.unwrap_or(DEFAULT_TAB_HISTORY_LIMIT);
+    format!("window.__xprojTabHistoryLimit = {};", limit)
+}
+
+// Inject the selected theme, the tab manager (unless the user disabled it) and the header offset fix into a page
+fn inject_page_scripts(window: &tauri::WebviewWindow, tag: &str) {
+    if let Err(e) = window.eval(FORUM_SPA_HOOK_JS) {
+        eprintln!("[{}] Failed to inject navigation hook: {}", tag, e);
+    }
+    forum_theme::apply(window);
+    power::apply(window);
+    if settings::current(window.app_handle()).tab_bar_disabled {
+        return;
+    }
+    if let Err(e) = window.eval(tab_history_limit_js(window.app_handle())) {
+        eprintln!("[{}] Failed to set tab history limit: {}", tag, e);
+    }
+    if let Err(e) = window.eval(FORUM_TAB_MANAGER_JS) {
+        eprintln!("[{}] Failed to inject tab manager: {}", tag, e);
+        return;
+    }
+    if let Err(e) = window.eval(FORUM_HEADER_OFFSET_JS) {
+        eprintln!("[{}] Failed to inject header offset fix: {}", tag, e);
+    }
+    // With the login probe pending, it opens the queued tabs once its bridge reply is in
+    if window.label() == "forum" && !forum_login_probe_pending(window) {
+        forum_session::apply_pending(window);
+    }
+}
+
 #[tauri::command]
 async fn open_forum_with_login(app: tauri::AppHandle, uid: String, auth: String) -> Result<(), String> {
     // The login endpoint that accepts POST data
@@ -788,8 +2590,9 @@ async fn open_forum_with_login(app: tauri::AppHandle, uid: String, auth: String)
         return Ok(());
     }
     
-    // Clone app handle for use in the on_new_window closure
+    // Clone app handle for use in the on_new_window and on_navigation closures
     let app_handle = app.clone();
+    let app_nav = app.clone();
     
     // Create a new webview window - start with a blank page, then POST
     let blank_url: Url = "about:blank".parse().map_err(|e: url::ParseError| e.to_string())?;
@@ -803,6 +2606,7 @@ async fn open_forum_with_login(app: tauri::AppHandle, uid: String, auth: String)
     .min_inner_size(800.0, 600.0)
     .center()
     .user_agent(BROWSER_USER_AGENT)
+    .theme(color_scheme_theme(&app))
     .devtools(cfg!(feature = "devtools"))
     // Inject JavaScript for multi-tab browser interface on page load
     // Note: initialization_script only runs on the first page, so we use on_page_load
@@ -815,10 +2619,12 @@ async fn open_forum_with_login(app: tauri::AppHandle, uid: String, auth: String)
             // Skip about:blank
             if url != "about:blank" {
                 println!("[Forum] Page loaded: {}, injecting tab manager", url);
+                forum_bridge::clear_spa_url(window.app_handle(), window.label());
                 // Inject the tab manager script
-                if let Err(e) = window.eval(FORUM_TAB_MANAGER_JS) {
-                    eprintln!("[Forum] Failed to inject tab manager: {}", e);
-                }
+                inject_page_scripts(&window, "Forum");
+                apply_host_zoom(&window, &url);
+                checkin::detect_result(&window, &url);
+                detect_forum_login_state(&window);
             }
         }
     })
@@ -844,14 +2650,22 @@ async fn open_forum_with_login(app: tauri::AppHandle, uid: String, auth: String)
     })
     // Allow navigation within the forum WebView window
     // Only allow navigation to trusted domains for security
-    .on_navigation(|url| {
-        let url_str = url.as_str();
+    .on_navigation(move |url| {
+        // Results reported back by injected scripts never navigate
+        if forum_bridge::handle_navigation(&app_nav, "forum", url) {
+            return false;
+        }
+        // Server invite links join the server instead of navigating
+        if handle_server_invite_link(&app_nav, url) {
+            return false;
+        }
+        // Blocklisted pages are refused even on allowed domains
+        if url_blocklist::check_navigation(&app_nav, url) {
+            return false;
+        }
+        check_forum_session_expired(&app_nav, url);
         // Allow about:blank for initial page and forum domain
-        url_str.starts_with("about:") ||
-        url_str.starts_with("https://bbs.upkk.com") ||
-        url_str.starts_with("http://bbs.upkk.com") ||
-        url_str.starts_with("https://servers.upkk.com") ||
-        url_str.starts_with("http://servers.upkk.com")
+        is_allowed_navigation(url)
     })
     .build()
     .map_err(|e| e.to_string())?;
@@ -862,6 +2676,58 @@ async fn open_forum_with_login(app: tauri::AppHandle, uid: String, auth: String)
     Ok(())
 }
 
+// Primary monitor details, returned by get_display_info
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct DisplayInfo {
+    pub name: Option<String>,
+    // Physical pixels
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f64,
+    // Size in logical pixels (what window sizes are given in)
+    pub logical_width: f64,
+    pub logical_height: f64,
+}
+
+// Share of the primary monitor a new browser window covers, and the bounds on its size (logical pixels)
+const BROWSER_WINDOW_SCREEN_FRACTION: f64 = 0.7;
+const BROWSER_WINDOW_MIN_SIZE: (f64, f64) = (800.0, 600.0);
+const BROWSER_WINDOW_MAX_SIZE: (f64, f64) = (1920.0, 1280.0);
+
+fn primary_display(app: &tauri::AppHandle) -> Option<DisplayInfo> {
+    let monitor = app.primary_monitor().ok().flatten()?;
+    let size = monitor.size();
+    let logical = size.to_logical::<f64>(monitor.scale_factor());
+    Some(DisplayInfo {
+        name: monitor.name().cloned(),
+        width: size.width,
+        height: size.height,
+        scale_factor: monitor.scale_factor(),
+        logical_width: logical.width,
+        logical_height: logical.height,
+    })
+}
+
+// Initial size of a browser window: a share of the primary monitor, clamped to sane bounds
+// (1200x800 when the monitor can't be determined)
+fn default_browser_window_size(app: &tauri::AppHandle) -> (f64, f64) {
+    match primary_display(app) {
+        Some(display) => (
+            (display.logical_width * BROWSER_WINDOW_SCREEN_FRACTION)
+                .clamp(BROWSER_WINDOW_MIN_SIZE.0, BROWSER_WINDOW_MAX_SIZE.0),
+            (display.logical_height * BROWSER_WINDOW_SCREEN_FRACTION)
+                .clamp(BROWSER_WINDOW_MIN_SIZE.1, BROWSER_WINDOW_MAX_SIZE.1),
+        ),
+        None => (1200.0, 800.0),
+    }
+}
+
+// Get the primary monitor's size and scale factor
+#[tauri::command]
+async fn get_display_info(app: tauri::AppHandle) -> Result<DisplayInfo, String> {
+    primary_display(&app).ok_or_else(|| "无法获取主显示器信息".to_string())
+}
+
 #[tauri::command]
 async fn open_url_in_browser_window(app: tauri::AppHandle, window_label: String, url: String, title: String) -> Result<(), String> {
     // Check if window already exists
@@ -873,22 +2739,30 @@ async fn open_url_in_browser_window(app: tauri::AppHandle, window_label: String,
         return Ok(());
     }
     
-    // Clone app handle and window_label for use in the on_new_window closure
+    // Clone app handle and window_label for use in the on_new_window and on_navigation closures
     let app_handle = app.clone();
+    let app_nav = app.clone();
     let label_clone = window_label.clone();
+    let label_nav = window_label.clone();
     
-    // Create a new webview window with full WebView2 capabilities
+    // Create a new webview window with full WebView2 capabilities, sized for the monitor
     let parsed_url: Url = url.parse().map_err(|e: url::ParseError| e.to_string())?;
+    // A newly opened forum window probes the login state on its first page load
+    if window_label == "forum" {
+        app.state::<ForumLoginProbe>().0.store(true, std::sync::atomic::Ordering::SeqCst);
+    }
+    let (width, height) = default_browser_window_size(&app);
     let _window = tauri::WebviewWindowBuilder::new(
         &app,
         &window_label,
         tauri::WebviewUrl::External(parsed_url),
     )
     .title(&title)
-    .inner_size(1200.0, 800.0)
-    .min_inner_size(800.0, 600.0)
+    .inner_size(width, height)
+    .min_inner_size(BROWSER_WINDOW_MIN_SIZE.0, BROWSER_WINDOW_MIN_SIZE.1)
     .center()
     .user_agent(BROWSER_USER_AGENT)
+    .theme(color_scheme_theme(&app))
     .devtools(cfg!(feature = "devtools"))
     // Inject JavaScript for multi-tab browser interface on page load
     .on_page_load(|window, payload| {
@@ -896,9 +2770,11 @@ async fn open_url_in_browser_window(app: tauri::AppHandle, window_label: String,
             let url = payload.url().to_string();
             if url != "about:blank" {
                 println!("[Browser] Page loaded: {}, injecting tab manager", url);
-                if let Err(e) = window.eval(FORUM_TAB_MANAGER_JS) {
-                    eprintln!("[Browser] Failed to inject tab manager: {}", e);
-                }
+                forum_bridge::clear_spa_url(window.app_handle(), window.label());
+                inject_page_scripts(&window, "Browser");
+                apply_host_zoom(&window, &url);
+                checkin::detect_result(&window, &url);
+                detect_forum_login_state(&window);
             }
         }
     })
@@ -924,17 +2800,31 @@ async fn open_url_in_browser_window(app: tauri::AppHandle, window_label: String,
     })
     // Allow navigation within external browser windows
     // Only allow navigation to trusted domains for security
-    .on_navigation(|url| {
-        let url_str = url.as_str();
+    .on_navigation(move |url| {
+        // Results reported back by injected scripts never navigate
+        if forum_bridge::handle_navigation(&app_nav, &label_nav, url) {
+            return false;
+        }
+        // Server invite links join the server instead of navigating
+        if handle_server_invite_link(&app_nav, url) {
+            return false;
+        }
+        // Blocklisted pages are refused even on allowed domains
+        if url_blocklist::check_navigation(&app_nav, url) {
+            return false;
+        }
+        check_forum_session_expired(&app_nav, url);
         // Allow about:blank and upkk domains
-        url_str.starts_with("about:") ||
-        url_str.starts_with("https://bbs.upkk.com") ||
-        url_str.starts_with("http://bbs.upkk.com") ||
-        url_str.starts_with("https://servers.upkk.com") ||
-        url_str.starts_with("http://servers.upkk.com")
+        is_allowed_navigation(url)
     })
     .build()
-    .map_err(|e| e.to_string())?;
+    .map_err(|e| {
+        // No window came up, so no page load will consume the probe flag
+        if window_label == "forum" {
+            app.state::<ForumLoginProbe>().0.store(false, std::sync::atomic::Ordering::SeqCst);
+        }
+        e.to_string()
+    })?;
     
     Ok(())
 }
@@ -968,6 +2858,7 @@ async fn open_steam_login(app: tauri::AppHandle, login_url: String) -> Result<()
     .min_inner_size(600.0, 500.0)
     .center()
     .user_agent(BROWSER_USER_AGENT)
+    .theme(color_scheme_theme(&app))
     .on_navigation(move |url| {
         let url_str = url.to_string();
         
@@ -1022,11 +2913,22 @@ async fn open_steam_login(app: tauri::AppHandle, login_url: String) -> Result<()
 }
 
 #[tauri::command]
-async fn open_checkin_page(app: tauri::AppHandle) -> Result<(), String> {
+async fn open_checkin_page(app: tauri::AppHandle, separate_window: Option<bool>) -> Result<(), String> {
     // Open the check-in page directly in the forum WebView2 window
     // This allows the check-in to use the forum's cookies/session
-    let checkin_url = "https://bbs.upkk.com/plugin.php?id=xnet_core_api:xproj_sign".to_string();
-    open_url_in_browser_window(app, "forum".to_string(), checkin_url, "Upkk 社区论坛 - 签到".to_string()).await
+    // With separate_window the page opens in its own "checkin" window instead, so the forum
+    // stays on the page the user was reading; all windows share the same webview profile,
+    // so the forum session cookies still apply
+    // The page's result is reported back through the checkin-result event (see checkin::detect_result)
+    let checkin_url = checkin::CHECKIN_URL.to_string();
+    let label = if separate_window.unwrap_or(false) { "checkin" } else { "forum" };
+    open_url_in_browser_window(app, label.to_string(), checkin_url, "Upkk 社区论坛 - 签到".to_string()).await
+}
+
+// User-Agent the browser windows send (baked in at build time, custom builds may differ)
+#[tauri::command]
+async fn get_browser_user_agent() -> Result<String, String> {
+    Ok(BROWSER_USER_AGENT.to_string())
 }
 
 #[tauri::command]
@@ -1037,10 +2939,63 @@ async fn close_window(app: tauri::AppHandle, window_label: String) -> Result<(),
     Ok(())
 }
 
+// Keep a browser window's OS title in sync with its active tab
+#[tauri::command]
+async fn set_window_title(app: tauri::AppHandle, window_label: String, title: String) -> Result<(), String> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err("窗口标题不能为空".to_string());
+    }
+    if let Some(window) = app.get_webview_window(&window_label) {
+        window.set_title(title).map_err(|e| e.to_string())
+    } else {
+        Err(format!("窗口未打开: {}", window_label))
+    }
+}
+
+// Window in which rapid forum_navigate calls are coalesced (only the latest is applied)
+const NAVIGATION_DEBOUNCE: Duration = Duration::from_millis(150);
+
+// Managed state for coalescing forum navigations: sequence number and target of the latest request
+#[derive(Default)]
+struct ForumNavigation {
+    seq: std::sync::atomic::AtomicU64,
+    latest: Mutex<String>,
+}
+
+// Wait out the debounce window; returns false if a newer navigation arrived meanwhile
+async fn debounce_navigation(app: &tauri::AppHandle, target: &str) -> bool {
+    use std::sync::atomic::Ordering;
+    
+    let nav = app.state::<ForumNavigation>();
+    let seq = nav.seq.fetch_add(1, Ordering::SeqCst) + 1;
+    *nav.latest.lock().unwrap_or_else(|e| e.into_inner()) = target.to_string();
+    
+    tokio::time::sleep(NAVIGATION_DEBOUNCE).await;
+    
+    if nav.seq.load(Ordering::SeqCst) != seq {
+        let latest = nav.latest.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        println!("[Forum] Coalesced navigation to {} (superseded by {})", target, latest);
+        return false;
+    }
+    true
+}
+
 #[tauri::command]
 async fn forum_navigate(app: tauri::AppHandle, url: String) -> Result<(), String> {
     if let Some(window) = app.get_webview_window("forum") {
-        let parsed_url: Url = url.parse().map_err(|e: url::ParseError| e.to_string())?;
+        let parsed_url = normalize_url(&url)?;
+        // Reject disallowed domains up front - on_navigation would silently cancel them
+        if !is_allowed_navigation(&parsed_url) {
+            return Err(format!("{}: 不允许访问该网址: {}", NAVIGATION_BLOCKED, parsed_url));
+        }
+        if url_blocklist::check_navigation(&app, &parsed_url) {
+            return Err(format!("{}: 该网址已被列入黑名单: {}", NAVIGATION_BLOCKED, parsed_url));
+        }
+        // Rapid clicks race each other - only the latest request within the window is applied
+        if !debounce_navigation(&app, parsed_url.as_str()).await {
+            return Ok(());
+        }
         window.navigate(parsed_url).map_err(|e| e.to_string())?;
         Ok(())
     } else {
@@ -1081,6 +3036,10 @@ async fn forum_go_forward(app: tauri::AppHandle) -> Result<(), String> {
 #[tauri::command]
 async fn forum_get_url(app: tauri::AppHandle) -> Result<String, String> {
     if let Some(window) = app.get_webview_window("forum") {
+        // Prefer the URL reported by in-page navigation, the webview may not track pushState
+        if let Some(url) = forum_bridge::spa_url(&app, "forum") {
+            return Ok(url);
+        }
         window.url().map(|u| u.to_string()).map_err(|e| e.to_string())
     } else {
         Err("论坛窗口未打开".to_string())
@@ -1088,13 +3047,196 @@ async fn forum_get_url(app: tauri::AppHandle) -> Result<String, String> {
 }
 
 #[tauri::command]
-async fn write_text_file(path: String, contents: String) -> Result<(), String> {
-    let p = std::path::Path::new(&path);
-    // Only allow writing .json files (used for favorites export)
+async fn forum_set_fullscreen(app: tauri::AppHandle, enabled: bool) -> Result<bool, String> {
+    if let Some(window) = app.get_webview_window("forum") {
+        window.set_fullscreen(enabled).map_err(|e| e.to_string())?;
+        let fullscreen = window.is_fullscreen().map_err(|e| e.to_string())?;
+        // The tab bar is fixed inside the page so body padding-top stays at 40px;
+        // just let the page re-layout and tell the UI about the new state
+        let _ = window.eval("window.dispatchEvent(new Event('resize'))");
+        let _ = app.emit("forum-fullscreen-changed", fullscreen);
+        Ok(fullscreen)
+    } else {
+        Err("论坛窗口未打开".to_string())
+    }
+}
+
+// Allowed webview zoom factors
+const ZOOM_RANGE: std::ops::RangeInclusive<f64> = 0.25..=5.0;
+
+// Saved zoom factor for a hostname (1.0 when none is saved)
+fn zoom_for_host(app: &tauri::AppHandle, host: &str) -> f64 {
+    settings::current(app)
+        .zoom_by_host
+        .get(&host.to_ascii_lowercase())
+        .copied()
+        .unwrap_or(1.0)
+}
+
+// Re-apply the saved zoom for the loaded page's host (called from on_page_load)
+fn apply_host_zoom(window: &tauri::WebviewWindow, url: &str) {
+    let host = match Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_string)) {
+        Some(host) => host,
+        None => return,
+    };
+    if let Err(e) = window.set_zoom(zoom_for_host(window.app_handle(), &host)) {
+        log::warn!("[Zoom] Failed to set zoom for {}: {}", host, e);
+    }
+}
+
+// Zoom the forum window and remember the factor for the current page's host
+#[tauri::command]
+async fn forum_zoom(app: tauri::AppHandle, factor: f64) -> Result<(), String> {
+    if !ZOOM_RANGE.contains(&factor) {
+        return Err(format!("Zoom must be between {} and {}", ZOOM_RANGE.start(), ZOOM_RANGE.end()));
+    }
+    if let Some(window) = app.get_webview_window("forum") {
+        let url = match forum_bridge::spa_url(&app, "forum") {
+            Some(url) => url,
+            None => window.url().map(|u| u.to_string()).map_err(|e| e.to_string())?,
+        };
+        let host = Url::parse(&url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_ascii_lowercase))
+            .ok_or_else(|| "当前页面没有主机名".to_string())?;
+        window.set_zoom(factor).map_err(|e| e.to_string())?;
+        settings::update(&app, |s| {
+            s.zoom_by_host.insert(host, factor);
+        })?;
+        Ok(())
+    } else {
+        Err("论坛窗口未打开".to_string())
+    }
+}
+
+// Get the saved zoom factor for a hostname (1.0 for unknown hosts)
+#[tauri::command]
+async fn get_zoom_for_host(app: tauri::AppHandle, host: String) -> Result<f64, String> {
+    Ok(zoom_for_host(&app, host.trim()))
+}
+
+// Saved color scheme as a window theme (None = follow the OS)
+fn color_scheme_theme(app: &tauri::AppHandle) -> Option<tauri::Theme> {
+    match settings::current(app).color_scheme.as_deref() {
+        Some("light") => Some(tauri::Theme::Light),
+        Some("dark") => Some(tauri::Theme::Dark),
+        _ => None,
+    }
+}
+
+// Force browser windows (forum, pages, login) to a light or dark preferred color scheme,
+// or follow the OS with "system". Applied to open windows now and to new windows when built.
+// Pages see it through prefers-color-scheme where the platform webview supports it.
+#[tauri::command]
+async fn set_color_scheme(app: tauri::AppHandle, scheme: String) -> Result<(), String> {
+    let color_scheme = match scheme.as_str() {
+        "light" | "dark" => Some(scheme),
+        "system" => None,
+        other => return Err(format!("Unsupported color scheme: {} (expected light, dark or system)", other)),
+    };
+    settings::update(&app, |s| s.color_scheme = color_scheme)?;
+    
+    let theme = color_scheme_theme(&app);
+    for window in app.webview_windows().values() {
+        if window.label() != "main" {
+            if let Err(e) = window.set_theme(theme) {
+                log::warn!("[Theme] Failed to set color scheme for {}: {}", window.label(), e);
+            }
+        }
+    }
+    Ok(())
+}
+
+// Re-inject the tab manager when on_page_load missed it (e.g. SPA navigation without Finished)
+// The script's __xprojTabManager guard prevents double initialization
+// Refused while the user has the tab bar disabled
+#[tauri::command]
+async fn forum_reinject_tabbar(app: tauri::AppHandle) -> Result<(), String> {
+    if settings::current(&app).tab_bar_disabled {
+        return Err("标签栏已禁用".to_string());
+    }
+    if let Some(window) = app.get_webview_window("forum") {
+        window.eval(tab_history_limit_js(&app)).map_err(|e| e.to_string())?;
+        window.eval(FORUM_TAB_MANAGER_JS).map_err(|e| e.to_string())?;
+        window.eval(FORUM_HEADER_OFFSET_JS).map_err(|e| e.to_string())?;
+        Ok(())
+    } else {
+        Err("论坛窗口未打开".to_string())
+    }
+}
+
+// Enable or disable the injected tab bar; the forum window is reloaded to apply it
+#[tauri::command]
+async fn forum_toggle_tab_bar(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    settings::update(&app, |s| s.tab_bar_disabled = !enabled)?;
+    if let Some(window) = app.get_webview_window("forum") {
+        window.eval("window.location.reload()").map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// Set how many back/forward entries each tab keeps; applied to open windows right away,
+// existing longer histories are trimmed on their next navigation
+#[tauri::command]
+async fn set_tab_history_limit(app: tauri::AppHandle, limit: usize) -> Result<(), String> {
+    if !(1..=MAX_TAB_HISTORY_LIMIT).contains(&limit) {
+        return Err(format!("历史记录上限必须在 1 到 {} 之间", MAX_TAB_HISTORY_LIMIT));
+    }
+    settings::update(&app, |s| s.tab_history_limit = Some(limit))?;
+    let script = tab_history_limit_js(&app);
+    for window in app.webview_windows().values() {
+        if let Err(e) = window.eval(&script) {
+            log::warn!("[TabManager] Failed to update history limit for {}: {}", window.label(), e);
+        }
+    }
+    Ok(())
+}
+
+// Directories that file commands may read or write: where the save/open dialogs normally land
+// (documents, downloads, desktop) and the app's own data directory
+fn allowed_file_dirs(app: &tauri::AppHandle) -> Vec<std::path::PathBuf> {
+    let paths = app.path();
+    [paths.document_dir(), paths.download_dir(), paths.desktop_dir(), paths.app_data_dir()]
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter_map(|dir| dir.canonicalize().ok())
+        .collect()
+}
+
+// Check a file path passed in from the webview: the extension must be one of `extensions`
+// and the file must sit inside an allowed directory once symlinks and ".." are resolved.
+// Returns the resolved path, which is what the caller should open.
+fn check_user_file_path(app: &tauri::AppHandle, path: &str, extensions: &[&str]) -> Result<std::path::PathBuf, String> {
+    let p = std::path::Path::new(path);
     match p.extension().and_then(|e| e.to_str()) {
-        Some(ext) if ext.eq_ignore_ascii_case("json") => {}
-        _ => return Err("Only .json files are allowed".to_string()),
+        Some(ext) if extensions.iter().any(|allowed| ext.eq_ignore_ascii_case(allowed)) => {}
+        _ => {
+            let list: Vec<String> = extensions.iter().map(|ext| format!(".{}", ext)).collect();
+            return Err(format!("Only {} files are allowed", list.join("/")));
+        }
     }
+    // Files about to be written don't exist yet, so resolve their directory instead
+    let resolved = if p.exists() {
+        p.canonicalize()
+    } else {
+        let file_name = p.file_name().ok_or_else(|| "Invalid file path".to_string())?;
+        p.parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .ok_or_else(|| "File path must be absolute".to_string())?
+            .canonicalize()
+            .map(|dir| dir.join(file_name))
+    }
+    .map_err(|e| format!("Invalid file path: {}", e))?;
+    if !allowed_file_dirs(app).iter().any(|dir| resolved.starts_with(dir)) {
+        return Err("只能读写文档、下载、桌面或应用数据目录中的文件".to_string());
+    }
+    Ok(resolved)
+}
+
+#[tauri::command]
+async fn write_text_file(app: tauri::AppHandle, path: String, contents: String) -> Result<(), String> {
+    // Only allow writing .json files (used for favorites export)
+    let p = check_user_file_path(&app, &path, &["json"])?;
     std::fs::write(p, contents).map_err(|e| format!("Failed to write file: {}", e))
 }
 
@@ -1129,14 +3271,135 @@ async fn load_monitor_data(app: tauri::AppHandle) -> Result<String, String> {
         .map_err(|e| format!("Failed to read monitor data: {}", e))
 }
 
+// App data files removed by reset_app_state (and carried by backups)
+const APP_DATA_FILES: [&str; 10] = [
+    "credentials.enc",
+    os_auth::PASSPHRASE_FILE,
+    "settings.json",
+    "monitor_data.json",
+    "server_store.json",
+    "forum_tabs.json",
+    "favorites.json",
+    "url_blocklist.json",
+    "a2s_cache.json",
+    "last_results.json",
+];
+
+/// Factory reset: remove credentials, settings, monitor data, query history, caches
+/// and the fallback device ID. Returns the paths that were removed.
+/// Files the user exported outside the app directories are not touched.
+#[tauri::command]
+async fn reset_app_state(app: tauri::AppHandle) -> Result<Vec<String>, String> {
+    let mut removed = Vec::new();
+    
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    for name in APP_DATA_FILES {
+        let path = app_data_dir.join(name);
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+            removed.push(path.to_string_lossy().to_string());
+        }
+    }
+    
+    let cache_dir = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| format!("Failed to get app cache dir: {}", e))?;
+    if cache_dir.exists() {
+        std::fs::remove_dir_all(&cache_dir)
+            .map_err(|e| format!("Failed to remove {}: {}", cache_dir.display(), e))?;
+        removed.push(cache_dir.to_string_lossy().to_string());
+    }
+    
+    if let Some(path) = secure_storage::fallback_device_id_path().filter(|p| p.exists()) {
+        std::fs::remove_file(&path)
+            .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+        removed.push(path.to_string_lossy().to_string());
+    }
+    secure_storage::invalidate_device_id(&app);
+    
+    // Reset in-memory state so nothing stale is written back
+    favorites::stop(&app);
+    favorites::reload_keys(&app);
+    settings::reset(&app);
+    server_store::clear(&app);
+    if let Ok(mut entries) = app.state::<dns_cache::DnsCache>().0.lock() {
+        entries.clear();
+    }
+    if let Ok(mut entries) = app.state::<a2s_cache::A2SCache>().0.lock() {
+        entries.clear();
+    }
+    server_list::clear_cache(&app);
+    if let Ok(mut entries) = app.state::<url_blocklist::UrlBlocklist>().0.lock() {
+        entries.clear();
+    }
+    if let Ok(mut entries) = app.state::<a2s::ChallengeCache>().0.lock() {
+        entries.clear();
+    }
+    
+    log::info!("[Reset] App state reset, removed {} item(s)", removed.len());
+    let _ = app.emit("app-reset", &removed);
+    
+    Ok(removed)
+}
+
+// Link handed over by a second launch, emitted as deep-link-received
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct DeepLink {
+    pub url: String,
+}
+
+// A second launch was redirected here: bring the running instance forward and handle any link
+// on its command line. Server invite links emit join-request; connect links (see launch::connect_target)
+// go through launch::queue like links opened through the deep-link handler, emitting launch-join-request.
+#[cfg(desktop)]
+fn handle_second_instance(app: &tauri::AppHandle, argv: Vec<String>) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    
+    // argv[0] is the executable; file paths like C:\... parse as URLs too, so require "://"
+    let links = argv
+        .iter()
+        .skip(1)
+        .filter(|arg| arg.contains("://"))
+        .filter_map(|arg| Url::parse(arg).ok());
+    for url in links {
+        println!("[DeepLink] Received from second instance: {}", url);
+        let _ = app.emit("deep-link-received", DeepLink { url: url.to_string() });
+        if handle_server_invite_link(app, &url) {
+            continue;
+        }
+        if let Some(join) = launch::connect_target(&url) {
+            launch::queue(app, join, true);
+        }
+    }
+}
+
+// Size at which the log file is rotated (the previous file is kept)
+const LOG_FILE_MAX_BYTES: u128 = 1024 * 1024;
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-  tauri::Builder::default()
+  let builder = tauri::Builder::default();
+  // Must be the first plugin so a second launch exits before anything else starts
+  #[cfg(desktop)]
+  let builder = builder.plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+    handle_second_instance(app, argv);
+  }));
+  builder
     .plugin(tauri_plugin_shell::init())
     .plugin(tauri_plugin_http::init())
     .plugin(tauri_plugin_process::init())
     .plugin(tauri_plugin_notification::init())
     .plugin(tauri_plugin_dialog::init())
+    .plugin(tauri_plugin_deep_link::init())
     .invoke_handler(tauri::generate_handler![
         open_forum_window,
         open_forum_with_login,
@@ -1144,33 +3407,256 @@ pub fn run() {
         open_steam_login,
         open_checkin_page,
         close_window,
+        get_browser_user_agent,
+        set_window_title,
         forum_navigate,
         forum_reload,
         forum_go_back,
         forum_go_forward,
         forum_get_url,
+        forum_set_fullscreen,
+        forum_zoom,
+        get_zoom_for_host,
+        set_color_scheme,
+        get_display_info,
+        forum_toggle_tab_bar,
+        set_tab_history_limit,
+        forum_reinject_tabbar,
+        forum_theme::forum_list_themes,
+        forum_theme::forum_set_theme,
+        forum_check_session,
+        set_forum_auto_reauth,
+        forum_get_tabs,
+        make_forum_server_link,
+        forum_session::forum_restore_tabs,
+        forum_session::forum_open_tabs,
+        forum_session::set_forum_tab_restore,
         query_server_a2s,
+        set_query_source_ip,
+        set_population_thresholds,
+        set_max_concurrency,
+        set_udp_recv_buffer,
+        set_query_relay,
+        server_summary,
+        verify_server_ownership,
+        probe_server,
+        stability_score,
+        compare_servers,
+        query_human_players,
+        query_server_a2s_tcp,
+        server_badge,
+        set_network_paused,
+        is_network_paused,
+        query_matches_filter,
+        query_and_join,
+        normalize_server_list,
+        query_servers_a2s,
+        favorites::get_favorites,
+        favorites::set_favorites,
+        favorites::start_favorites_autorefresh,
+        favorites::stop_favorites_autorefresh,
+        favorites::import_servers_txt,
+        url_blocklist::update_url_blocklist,
+        power::set_power_saver,
+        dns_cache::resolve_hosts,
+        dns_cache::reverse_dns,
+        steam::is_steam_running,
+        steam::launch_game,
+        a2s::fetch_server_logo,
+        a2s::list_regions,
+        #[cfg(feature = "devtools")]
+        a2s::raw_a2s_query,
+        #[cfg(feature = "devtools")]
+        a2s::test_a2s_challenge,
+        #[cfg(feature = "devtools")]
+        a2s::a2s_debug_dump,
+        server_store::get_server_uptime,
+        server_store::get_average_ping,
+        server_store::get_server_age,
+        server_store::set_server_overrides,
+        server_store::get_server_overrides,
+        a2s_cache::prune_a2s_cache,
+        a2s_cache::a2s_cache_stats,
+        a2s_cache::persist_a2s_cache,
+        a2s_cache::get_cached_results,
+        a2s_cache::load_last_results,
+        server_list::fetch_server_list,
+        server_list::merge_server_data,
+        server_list::group_servers_by_host,
+        server_list::find_servers_by_map,
         // Secure credential storage commands
         secure_storage::save_credentials,
         secure_storage::load_credentials,
         secure_storage::clear_credentials,
         secure_storage::get_device_fingerprint,
+        secure_storage::regenerate_device_id,
+        secure_storage::get_key_derivation_status,
         secure_storage::has_stored_credentials,
+        secure_storage::validate_stored_credentials,
+        secure_storage::get_securecode_authenticated,
+        secure_storage::set_securecode_os_auth,
+        secure_storage::get_last_account,
+        secure_storage::set_last_account,
         write_text_file,
         // Monitor data persistence commands
         save_monitor_data,
-        load_monitor_data
+        load_monitor_data,
+        // Settings commands
+        settings::get_settings,
+        diagnostics::export_diagnostics,
+        diagnostics::get_webview_backend,
+        clock::check_clock_sanity,
+        reset_app_state,
+        backup::export_backup,
+        backup::import_backup,
+        memory::get_memory_stats,
+        memory::reclaim_memory,
+        notifications::get_notification_settings,
+        notifications::set_notification_settings,
+        notifications::test_notification,
+        metrics::servers_metrics_text,
+        geoip::server_flag,
+        geoip::set_geoip_database,
+        launch::take_launch_join_request,
+        query_failures::get_recent_query_failures
     ])
+    // Auto-login once the main window has loaded so the frontend is listening for credentials-ready
+    .on_page_load(|webview, payload| {
+      if webview.label() == "main" {
+        if let tauri::webview::PageLoadEvent::Finished = payload.event() {
+          secure_storage::auto_login(webview.app_handle());
+        }
+      }
+    })
+    .on_window_event(|window, event| {
+      // Save the forum tabs before the window closes so they can be restored next time
+      if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+        let app = window.app_handle().clone();
+        if window.label() == "forum" && settings::current(&app).restore_forum_tabs {
+          if let Some(forum) = app.get_webview_window("forum") {
+            api.prevent_close();
+            tauri::async_runtime::spawn(async move {
+              if let Err(e) = forum_session::save_session(&app, &forum).await {
+                log::warn!("[ForumSession] {}", e);
+              }
+              let _ = forum.destroy();
+            });
+          }
+        }
+      }
+    })
     .setup(|app| {
-      if cfg!(debug_assertions) {
-        app.handle().plugin(
-          tauri_plugin_log::Builder::default()
-            .level(log::LevelFilter::Info)
-            .build(),
-        )?;
+      // Logs are written to the app log dir in every build, since diagnostics exports read them
+      // back (read_log_tail); debug builds also print them
+      use tauri_plugin_log::{Target, TargetKind};
+      let log_targets = if cfg!(debug_assertions) {
+        vec![Target::new(TargetKind::Stdout), Target::new(TargetKind::LogDir { file_name: None })]
+      } else {
+        vec![Target::new(TargetKind::LogDir { file_name: None })]
+      };
+      app.handle().plugin(
+        tauri_plugin_log::Builder::default()
+          .level(log::LevelFilter::Info)
+          .targets(log_targets)
+          .max_file_size(LOG_FILE_MAX_BYTES)
+          .rotation_strategy(tauri_plugin_log::RotationStrategy::KeepOne)
+          .build(),
+      )?;
+      let app_settings = settings::load_settings(app.handle());
+      app.manage(settings::SettingsState(Mutex::new(app_settings)));
+      app.manage(secure_storage::DeviceKeyCache::default());
+      app.manage(secure_storage::AutoLoginDone::default());
+      secure_storage::warm_up(app.handle());
+      app.manage(dns_cache::DnsCache::default());
+      app.manage(a2s_cache::load(app.handle()));
+      app.manage(a2s::ChallengeCache::default());
+      app.manage(server_list::ServerListCache::default());
+      app.manage(forum_bridge::ForumBridge::default());
+      app.manage(forum_session::ForumSession::default());
+      app.manage(ForumNavigation::default());
+      app.manage(ForumLoginProbe::default());
+      app.manage(NetworkPause::default());
+      app.manage(favorites::FavoritesRefresh::default());
+      app.manage(favorites::load_keys(app.handle()));
+      app.manage(notifications::ServerWatch::default());
+      app.manage(geoip::GeoIp::default());
+      app.manage(launch::LaunchJoin::default());
+      app.manage(query_failures::QueryFailures::default());
+      launch::init(app.handle());
+      app.manage(url_blocklist::load(app.handle()));
+      app.manage(power::PowerSaver::default());
+      power::start(app.handle());
+      if let Some(interval_secs) = settings::current(app.handle()).favorites_refresh_interval_secs {
+        favorites::start(app.handle(), interval_secs);
       }
+      app.manage(server_store::load(app.handle()));
+      
+      // Periodically persist the server store (query history) to disk, off the async runtime
+      let store_handle = app.handle().clone();
+      tauri::async_runtime::spawn(async move {
+        loop {
+          tokio::time::sleep(Duration::from_secs(60)).await;
+          let handle = store_handle.clone();
+          let _ = tauri::async_runtime::spawn_blocking(move || server_store::flush(&handle)).await;
+        }
+      });
       Ok(())
     })
-    .run(tauri::generate_context!())
-    .expect("error while running tauri application");
+    .build(tauri::generate_context!())
+    .expect("error while building tauri application")
+    .run(|app, event| {
+      // Persist pending server store changes before exiting
+      if let tauri::RunEvent::Exit = event {
+        server_store::flush(app);
+      }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A2S_INFO reply captured from a CS2 dedicated server (EDF carries the game port)
+    const CS2_INFO_REPLY: &[u8] = b"\xFF\xFF\xFF\xFFI\x11[UPKK] ZE #1\0ze_paranoid_rezurrection_v11_9\0csgo\0Counter-Strike 2\0\xDA\x02\x1F\x40\x02dl\0\x011.40.8.1\0\x80\x87\x69";
+
+    #[test]
+    fn parses_captured_cs2_info_reply() {
+        let mut result = A2SQueryResult::default();
+        parse_a2s_info(CS2_INFO_REPLY, &mut result).unwrap();
+
+        assert_eq!(result.name, "[UPKK] ZE #1");
+        assert_eq!(result.map_name, "ze_paranoid_rezurrection_v11_9");
+        assert_eq!(result.game, "Counter-Strike 2");
+        assert_eq!(result.app_id, 730);
+        assert_eq!((result.players, result.max_players, result.bots), (31, 64, 2));
+        assert_eq!(result.real_players, 29);
+        assert_eq!(result.server_type, "dedicated");
+        assert_eq!(result.environment, "Linux");
+        assert!(!result.password);
+        assert!(result.vac);
+        assert_eq!(result.version, "1.40.8.1");
+        assert_eq!(result.engine, "cs2");
+    }
+
+    #[test]
+    fn query_timeout_is_cut_short_by_the_deadline() {
+        let per_reply = Duration::from_secs(3);
+        assert_eq!(QueryTimeout { per_reply, deadline: None }.next_reply(), Some(per_reply));
+
+        let deadline = Some(Instant::now() + Duration::from_millis(500));
+        let capped = QueryTimeout { per_reply, deadline }.next_reply().unwrap();
+        assert!(capped <= Duration::from_millis(500));
+
+        let passed = Some(Instant::now() - Duration::from_millis(1));
+        assert_eq!(QueryTimeout { per_reply, deadline: passed }.next_reply(), None);
+    }
+
+    #[test]
+    fn detect_engine_splits_app_730_on_version() {
+        assert_eq!(detect_engine(730, "1.38.8.1"), "csgo");
+        assert_eq!(detect_engine(730, "1.39.0.0"), "cs2");
+        assert_eq!(detect_engine(730, "1.41.2.5"), "cs2");
+        assert_eq!(detect_engine(730, ""), "cs2");
+        assert_eq!(detect_engine(240, "7.1.0.0"), "css");
+    }
 }
@@ -0,0 +1,235 @@
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+/// Windows reclaim_memory never closes: the app itself, the forum and the login window
+const PROTECTED_WINDOWS: [&str; 3] = ["main", "forum", "steam_login"];
+
+/// How long closed windows get to tear down their webview processes before re-measuring
+const RECLAIM_SETTLE_DELAY: Duration = Duration::from_millis(1500);
+
+/// Resident memory of one child (webview) process
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ProcessMemory {
+    pub pid: u32,
+    pub name: String,
+    pub rss_bytes: u64,
+}
+
+/// An open window; memory can't be attributed to individual windows reliably,
+/// so only the webview processes as a whole are measured
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct WindowInfo {
+    pub label: String,
+    pub url: Option<String>,
+    pub focused: bool,
+}
+
+/// Result of get_memory_stats. Byte counts are None where the platform doesn't expose them.
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct MemoryStats {
+    /// Resident memory of the app process itself
+    pub process_rss_bytes: Option<u64>,
+    /// Sum over child processes (webview renderers, GPU/network helpers)
+    pub webview_rss_bytes: Option<u64>,
+    pub total_rss_bytes: Option<u64>,
+    pub webview_processes: Vec<ProcessMemory>,
+    pub windows: Vec<WindowInfo>,
+}
+
+/// Payload of the memory-reclaimed event, also returned by reclaim_memory
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct MemoryReclaimed {
+    pub closed_windows: Vec<String>,
+    /// Drop in total resident memory; None when memory can't be measured
+    pub freed_bytes: Option<u64>,
+}
+
+/// One row of the process table: pid, parent pid, name, resident bytes
+struct ProcessEntry {
+    pid: u32,
+    ppid: u32,
+    name: String,
+    rss_bytes: u64,
+}
+
+#[cfg(target_os = "linux")]
+fn process_table() -> Option<Vec<ProcessEntry>> {
+    let mut table = Vec::new();
+    for entry in std::fs::read_dir("/proc").ok()?.flatten() {
+        let pid: u32 = match entry.file_name().to_str().and_then(|s| s.parse().ok()) {
+            Some(pid) => pid,
+            None => continue,
+        };
+        let status = match std::fs::read_to_string(entry.path().join("status")) {
+            Ok(status) => status,
+            Err(_) => continue,
+        };
+        let field = |key: &str| {
+            status
+                .lines()
+                .find_map(|line| line.strip_prefix(key))
+                .map(|value| value.trim().to_string())
+        };
+        let ppid = field("PPid:").and_then(|v| v.parse().ok()).unwrap_or(0);
+        // VmRSS is reported in kB; kernel threads have none
+        let rss_kb: u64 = field("VmRSS:")
+            .and_then(|v| v.split_whitespace().next().and_then(|n| n.parse().ok()))
+            .unwrap_or(0);
+        table.push(ProcessEntry {
+            pid,
+            ppid,
+            name: field("Name:").unwrap_or_default(),
+            rss_bytes: rss_kb * 1024,
+        });
+    }
+    Some(table)
+}
+
+#[cfg(target_os = "macos")]
+fn process_table() -> Option<Vec<ProcessEntry>> {
+    // rss is in kB; comm goes last since it may contain spaces
+    let output = std::process::Command::new("ps")
+        .args(["-A", "-o", "pid=,ppid=,rss=,comm="])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    Some(
+        text.lines()
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let pid = parts.next()?.parse().ok()?;
+                let ppid = parts.next()?.parse().ok()?;
+                let rss_kb: u64 = parts.next()?.parse().ok()?;
+                let name = parts.collect::<Vec<_>>().join(" ");
+                Some(ProcessEntry { pid, ppid, name, rss_bytes: rss_kb * 1024 })
+            })
+            .collect(),
+    )
+}
+
+#[cfg(windows)]
+fn process_table() -> Option<Vec<ProcessEntry>> {
+    let output = crate::steam::hidden_command("powershell")
+        .args([
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "Get-CimInstance Win32_Process | ForEach-Object { \"$($_.ProcessId)`t$($_.ParentProcessId)`t$($_.WorkingSetSize)`t$($_.Name)\" }",
+        ])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    Some(
+        text.lines()
+            .filter_map(|line| {
+                let mut parts = line.trim().splitn(4, '\t');
+                let pid = parts.next()?.parse().ok()?;
+                let ppid = parts.next()?.parse().ok()?;
+                let rss_bytes = parts.next()?.parse().ok()?;
+                let name = parts.next().unwrap_or_default().to_string();
+                Some(ProcessEntry { pid, ppid, name, rss_bytes })
+            })
+            .collect(),
+    )
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn process_table() -> Option<Vec<ProcessEntry>> {
+    None
+}
+
+/// Resident memory of this process and of all its descendants.
+/// Blocking (may run a helper process), so call it from spawn_blocking.
+fn measure() -> (Option<u64>, Vec<ProcessMemory>) {
+    let table = match process_table() {
+        Some(table) => table,
+        None => return (None, Vec::new()),
+    };
+    let own_pid = std::process::id();
+    let own_rss = table.iter().find(|p| p.pid == own_pid).map(|p| p.rss_bytes);
+
+    // Walk the tree breadth-first; pids are unique so no cycle guard beyond `seen` is needed
+    let mut children = Vec::new();
+    let mut seen = vec![own_pid];
+    let mut index = 0;
+    while index < seen.len() {
+        let parent = seen[index];
+        for process in table.iter().filter(|p| p.ppid == parent && p.pid != parent) {
+            if !seen.contains(&process.pid) {
+                seen.push(process.pid);
+                children.push(ProcessMemory {
+                    pid: process.pid,
+                    name: process.name.clone(),
+                    rss_bytes: process.rss_bytes,
+                });
+            }
+        }
+        index += 1;
+    }
+    (own_rss, children)
+}
+
+async fn measure_async() -> (Option<u64>, Vec<ProcessMemory>) {
+    tokio::task::spawn_blocking(measure).await.unwrap_or((None, Vec::new()))
+}
+
+fn total(process_rss: Option<u64>, children: &[ProcessMemory]) -> Option<u64> {
+    process_rss.map(|own| own + children.iter().map(|p| p.rss_bytes).sum::<u64>())
+}
+
+/// Memory used by the app process and its webview processes, plus the open windows
+#[tauri::command]
+pub async fn get_memory_stats(app: tauri::AppHandle) -> Result<MemoryStats, String> {
+    let (process_rss_bytes, webview_processes) = measure_async().await;
+    let windows = app
+        .webview_windows()
+        .values()
+        .map(|window| WindowInfo {
+            label: window.label().to_string(),
+            url: window.url().ok().map(|url| url.to_string()),
+            focused: window.is_focused().unwrap_or(false),
+        })
+        .collect();
+    Ok(MemoryStats {
+        process_rss_bytes,
+        webview_rss_bytes: process_rss_bytes.map(|_| webview_processes.iter().map(|p| p.rss_bytes).sum()),
+        total_rss_bytes: total(process_rss_bytes, &webview_processes),
+        webview_processes,
+        windows,
+    })
+}
+
+/// Close browser windows that aren't focused (the main, forum and login windows are kept)
+/// and emit memory-reclaimed with how much resident memory that freed.
+/// Webviews don't expose a way to release memory of pages that stay open, so closing
+/// windows is the only lever available.
+#[tauri::command]
+pub async fn reclaim_memory(app: tauri::AppHandle) -> Result<MemoryReclaimed, String> {
+    let (before_rss, before_children) = measure_async().await;
+
+    let mut closed_windows = Vec::new();
+    for (label, window) in app.webview_windows() {
+        if PROTECTED_WINDOWS.contains(&label.as_str()) || window.is_focused().unwrap_or(false) {
+            continue;
+        }
+        match window.destroy() {
+            Ok(()) => closed_windows.push(label),
+            Err(e) => log::warn!("[Memory] Failed to close window {}: {}", label, e),
+        }
+    }
+
+    let freed_bytes = if closed_windows.is_empty() {
+        Some(0)
+    } else {
+        tokio::time::sleep(RECLAIM_SETTLE_DELAY).await;
+        let (after_rss, after_children) = measure_async().await;
+        total(before_rss, &before_children)
+            .zip(total(after_rss, &after_children))
+            .map(|(before, after)| before.saturating_sub(after))
+    };
+
+    log::info!("[Memory] Closed {} windows, freed {:?} bytes", closed_windows.len(), freed_bytes);
+    let reclaimed = MemoryReclaimed { closed_windows, freed_bytes };
+    let _ = app.emit("memory-reclaimed", reclaimed.clone());
+    Ok(reclaimed)
+}
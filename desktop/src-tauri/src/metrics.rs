@@ -0,0 +1,71 @@
+use std::fmt::Write;
+
+use crate::A2SQueryResult;
+
+/// Gauges written for each server: name, help text, value (None = not written for that server)
+type Gauge = (&'static str, &'static str, fn(&A2SQueryResult) -> Option<f64>);
+
+/// up is written for every server, the rest only for servers that answered
+const GAUGES: [Gauge; 6] = [
+    ("cs2_server_up", "Whether the server answered the A2S_INFO query", |r| {
+        Some(if r.success { 1.0 } else { 0.0 })
+    }),
+    ("cs2_server_ping_ms", "Round-trip time of the A2S_INFO query in milliseconds", |r| {
+        r.success.then_some(r.ping_ms as f64)
+    }),
+    ("cs2_server_players", "Players reported by the server, bots included", |r| {
+        r.success.then_some(r.players as f64)
+    }),
+    ("cs2_server_real_players", "Players reported by the server, bots excluded", |r| {
+        r.success.then_some(r.real_players as f64)
+    }),
+    ("cs2_server_bots", "Bots reported by the server", |r| r.success.then_some(r.bots as f64)),
+    ("cs2_server_max_players", "Player slots reported by the server", |r| {
+        r.success.then_some(r.max_players as f64)
+    }),
+];
+
+/// Escape a label value per the Prometheus text exposition format
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Label set identifying a server; map and name are only known for servers that answered
+fn labels(result: &A2SQueryResult) -> String {
+    let mut labels = format!("ip=\"{}\",port=\"{}\"", escape_label(&result.ip), escape_label(&result.port));
+    if result.success {
+        let _ = write!(
+            labels,
+            ",name=\"{}\",map=\"{}\"",
+            escape_label(&result.name),
+            escape_label(&result.map_name)
+        );
+    }
+    labels
+}
+
+/// Render query results in the Prometheus text exposition format
+fn render(results: &[A2SQueryResult]) -> String {
+    let mut text = String::new();
+    for (name, help, value) in GAUGES {
+        let _ = writeln!(text, "# HELP {} {}", name, help);
+        let _ = writeln!(text, "# TYPE {} gauge", name);
+        for result in results {
+            if let Some(value) = value(result) {
+                let _ = writeln!(text, "{}{{{}}} {}", name, labels(result), value);
+            }
+        }
+    }
+    text
+}
+
+/// Batch-query "ip:port" entries (like query_servers_a2s) and return the results as
+/// Prometheus exposition-format text with up/ping/player gauges, for dashboards and scrapers
+#[tauri::command]
+pub async fn servers_metrics_text(app: tauri::AppHandle, servers: Vec<String>) -> Result<String, String> {
+    let results = crate::query_batch(app, servers, None, None, None).await?;
+    Ok(render(&results))
+}
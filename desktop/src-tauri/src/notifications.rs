@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::plugin::PermissionState;
+use tauri::{Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::server_store::server_key;
+use crate::settings::{self, NotificationSettings};
+use crate::A2SQueryResult;
+
+/// Result of test_notification
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct NotificationTest {
+    /// "granted", "denied" or "prompt"
+    pub permission: String,
+    /// The sample notification was handed to the OS
+    pub sent: bool,
+    pub error: Option<String>,
+}
+
+/// Payload of the notification-permission-denied event
+#[derive(serde::Serialize, Clone, Debug)]
+struct NotificationPermissionDenied {
+    permission: String,
+    message: String,
+}
+
+/// Last state seen for a watched server
+struct WatchState {
+    online: bool,
+    map_name: String,
+    real_players: i32,
+    last_notified: Option<Instant>,
+}
+
+/// Managed state tracking favorite servers between autorefresh rounds
+#[derive(Default)]
+pub struct ServerWatch(Mutex<HashMap<String, WatchState>>);
+
+/// Notification text for the first enabled transition between two states, if any
+fn transition(
+    config: &NotificationSettings,
+    previous: &WatchState,
+    result: &A2SQueryResult,
+) -> Option<String> {
+    if config.online && previous.online != result.success {
+        return Some(if result.success { "服务器已上线" } else { "服务器已离线" }.to_string());
+    }
+    if !result.success {
+        return None;
+    }
+    if config.map_change && previous.map_name != result.map_name {
+        return Some(format!("地图已切换为 {}", result.map_name));
+    }
+    if config.threshold
+        && previous.real_players < config.player_threshold
+        && result.real_players >= config.player_threshold
+    {
+        return Some(format!("在线玩家已达到 {} 人", result.real_players));
+    }
+    None
+}
+
+/// Compare a refresh round's results with the previous round and notify about transitions.
+/// The first sighting of a server only records it; each server notifies at most once per cooldown.
+pub fn observe(app: &tauri::AppHandle, results: &[A2SQueryResult]) {
+    let config = settings::current(app).notifications;
+    let cooldown = Duration::from_secs(config.cooldown_secs);
+    let watch = app.state::<ServerWatch>();
+    let mut states = watch.0.lock().unwrap_or_else(|e| e.into_inner());
+
+    for result in results {
+        // Skipped queries say nothing about the server
+        if result.error_code.as_deref() == Some(crate::NETWORK_PAUSED) {
+            continue;
+        }
+        let key = server_key(&result.ip, &result.port);
+        let last_notified = match states.get(&key) {
+            Some(previous) => {
+                let message = if config.enabled { transition(&config, previous, result) } else { None };
+                match message {
+                    Some(_) if previous.last_notified.is_some_and(|at| at.elapsed() < cooldown) => {
+                        log::info!("[Notifications] {} transition within cooldown, suppressed", key);
+                        previous.last_notified
+                    }
+                    Some(message) => {
+                        let title = if result.name.is_empty() { key.clone() } else { result.name.clone() };
+                        if let Err(e) = app.notification().builder().title(title).body(message).show() {
+                            log::warn!("[Notifications] Failed to show notification: {}", e);
+                        }
+                        Some(Instant::now())
+                    }
+                    None => previous.last_notified,
+                }
+            }
+            None => None,
+        };
+        states.insert(key, WatchState {
+            online: result.success,
+            map_name: result.map_name.clone(),
+            real_players: result.real_players,
+            last_notified,
+        });
+    }
+}
+
+/// Check the OS notification permission before relying on notifications (when the autorefresh
+/// starts with notifications on, or they are switched on). Emits notification-permission-denied
+/// when notifications can't be shown, so the UI can warn instead of failing silently.
+pub fn check_permission(app: &tauri::AppHandle) {
+    let permission = match app.notification().permission_state() {
+        Ok(PermissionState::Granted) => return,
+        Ok(state) => state.to_string(),
+        Err(e) => {
+            log::warn!("[Notifications] Failed to read notification permission: {}", e);
+            return;
+        }
+    };
+    log::warn!("[Notifications] Notification permission is {}", permission);
+    let _ = app.emit("notification-permission-denied", NotificationPermissionDenied {
+        permission,
+        message: "系统通知权限未开启，收藏服务器的状态通知将无法显示".to_string(),
+    });
+}
+
+/// Request the notification permission and show a sample notification,
+/// returning the resulting permission state
+#[tauri::command]
+pub async fn test_notification(app: tauri::AppHandle) -> Result<NotificationTest, String> {
+    let permission = app
+        .notification()
+        .request_permission()
+        .map_err(|e| format!("Failed to request notification permission: {}", e))?;
+    if permission != PermissionState::Granted {
+        return Ok(NotificationTest { permission: permission.to_string(), sent: false, error: None });
+    }
+    let error = app
+        .notification()
+        .builder()
+        .title("通知测试")
+        .body("如果你看到这条消息，说明通知已正常开启")
+        .show()
+        .err()
+        .map(|e| e.to_string());
+    Ok(NotificationTest {
+        permission: permission.to_string(),
+        sent: error.is_none(),
+        error,
+    })
+}
+
+/// Get the favorite server notification settings
+#[tauri::command]
+pub async fn get_notification_settings(app: tauri::AppHandle) -> Result<NotificationSettings, String> {
+    Ok(settings::current(&app).notifications)
+}
+
+/// Replace the favorite server notification settings
+#[tauri::command]
+pub async fn set_notification_settings(
+    app: tauri::AppHandle,
+    notifications: NotificationSettings,
+) -> Result<NotificationSettings, String> {
+    if notifications.player_threshold < 1 {
+        return Err("player_threshold must be at least 1".to_string());
+    }
+    settings::update(&app, |s| s.notifications = notifications.clone())?;
+    if notifications.enabled {
+        check_permission(&app);
+    }
+    Ok(notifications)
+}
@@ -0,0 +1,163 @@
+use aes_gcm::aead::OsRng;
+use rand::RngCore;
+use sha2::Sha256;
+use std::fs;
+use std::path::PathBuf;
+use tauri::Manager;
+
+/// Error code returned when the platform has no usable OS authentication prompt
+pub const OS_AUTH_UNAVAILABLE: &str = "OsAuthUnavailable";
+
+/// Error code returned when the passphrase fallback is needed but none was given
+pub const PASSPHRASE_REQUIRED: &str = "PassphraseRequired";
+
+/// Error code returned when the given passphrase doesn't match the saved one
+pub const PASSPHRASE_MISMATCH: &str = "PassphraseMismatch";
+
+/// Shortest passphrase accepted for the fallback
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+/// PBKDF2-HMAC-SHA256 rounds for the passphrase hash, also what slows down guessing
+const PBKDF2_ROUNDS: u32 = 600_000;
+
+/// File in the app data directory holding the passphrase hash. Kept out of settings.json
+/// because get_settings hands the whole settings object to the webview.
+pub const PASSPHRASE_FILE: &str = "securecode_passphrase.json";
+
+/// Salted hash of the fallback passphrase (the passphrase itself is never saved)
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct PassphraseVerifier {
+    /// Hex-encoded random salt
+    pub salt: String,
+    /// Hex-encoded PBKDF2-HMAC-SHA256 of the passphrase
+    pub hash: String,
+}
+
+fn hash_passphrase(passphrase: &str, salt: &[u8]) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, PBKDF2_ROUNDS, &mut hash);
+    hash
+}
+
+/// Hash a new fallback passphrase under a fresh salt
+pub fn new_passphrase_verifier(passphrase: &str) -> Result<PassphraseVerifier, String> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(format!("密码至少需要 {} 个字符", MIN_PASSPHRASE_LEN));
+    }
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    Ok(PassphraseVerifier {
+        salt: hex::encode(salt),
+        hash: hex::encode(hash_passphrase(passphrase, &salt)),
+    })
+}
+
+fn get_verifier_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(PASSPHRASE_FILE))
+        .map_err(|e| format!("Failed to get app data dir: {}", e))
+}
+
+/// The saved fallback passphrase hash, None if no passphrase was set
+pub fn load_verifier(app: &tauri::AppHandle) -> Option<PassphraseVerifier> {
+    let json = fs::read_to_string(get_verifier_path(app).ok()?).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// Save the fallback passphrase hash, or remove it with None
+pub fn save_verifier(app: &tauri::AppHandle, verifier: Option<&PassphraseVerifier>) -> Result<(), String> {
+    let path = get_verifier_path(app)?;
+    match verifier {
+        Some(verifier) => {
+            let json = serde_json::to_string(verifier).map_err(|e| format!("Serialization failed: {}", e))?;
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(|e| format!("Failed to create app data dir: {}", e))?;
+            }
+            fs::write(&path, json).map_err(|e| format!("Failed to save passphrase: {}", e))
+        }
+        None if path.exists() => fs::remove_file(&path).map_err(|e| format!("Failed to remove passphrase: {}", e)),
+        None => Ok(()),
+    }
+}
+
+/// Check a passphrase against the saved verifier (constant-time comparison). Blocking.
+pub fn verify_passphrase(verifier: &PassphraseVerifier, passphrase: Option<&str>) -> Result<(), String> {
+    let passphrase = passphrase
+        .filter(|p| !p.is_empty())
+        .ok_or_else(|| format!("{}: 当前平台不支持系统身份验证，请输入凭据保护密码", PASSPHRASE_REQUIRED))?;
+    let (salt, expected) = match (hex::decode(&verifier.salt), hex::decode(&verifier.hash)) {
+        (Ok(salt), Ok(expected)) => (salt, expected),
+        _ => return Err("Saved passphrase hash is corrupted".to_string()),
+    };
+    let actual = hash_passphrase(passphrase, &salt);
+    let matches = expected.len() == actual.len()
+        && expected.iter().zip(&actual).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0;
+    if !matches {
+        return Err(format!("{}: 凭据保护密码错误", PASSPHRASE_MISMATCH));
+    }
+    Ok(())
+}
+
+/// Whether an error from verify_user means the platform has no prompt (so the passphrase
+/// fallback applies), as opposed to the user cancelling or failing it
+pub fn is_unavailable(error: &str) -> bool {
+    error.starts_with(OS_AUTH_UNAVAILABLE)
+}
+
+/// Authenticate the user: the OS prompt where there is one, otherwise the saved fallback
+/// passphrase. Blocks until the prompt is answered, so call it from spawn_blocking.
+pub fn authenticate(app: &tauri::AppHandle, passphrase: Option<&str>) -> Result<(), String> {
+    match verify_user() {
+        Ok(()) => Ok(()),
+        Err(e) if is_unavailable(&e) => match load_verifier(app) {
+            Some(verifier) => verify_passphrase(&verifier, passphrase),
+            None => Err(e),
+        },
+        Err(e) => Err(e),
+    }
+}
+
+/// Error code returned when the user cancelled or failed the OS authentication prompt
+#[cfg(windows)]
+pub const OS_AUTH_FAILED: &str = "OsAuthFailed";
+
+/// PowerShell script requesting Windows Hello verification through UserConsentVerifier.
+/// Prints the UserConsentVerificationResult name ("Verified" on success).
+#[cfg(windows)]
+const WINDOWS_HELLO_SCRIPT: &str = r#"
+Add-Type -AssemblyName System.Runtime.WindowsRuntime
+$asTask = ([System.WindowsRuntimeSystemExtensions].GetMethods() | Where-Object {
+    $_.Name -eq 'AsTask' -and $_.GetParameters().Count -eq 1 -and
+    $_.GetParameters()[0].ParameterType.Name -eq 'IAsyncOperation`1'
+})[0]
+[Windows.Security.Credentials.UI.UserConsentVerifier, Windows.Security.Credentials.UI, ContentType = WindowsRuntime] | Out-Null
+$op = [Windows.Security.Credentials.UI.UserConsentVerifier]::RequestVerificationAsync('Upkk 需要验证您的身份以读取已保存的登录凭据')
+$task = $asTask.MakeGenericMethod([Windows.Security.Credentials.UI.UserConsentVerificationResult]).Invoke($null, @($op))
+$task.Wait(-1) | Out-Null
+$task.Result
+"#;
+
+/// Ask the user to authenticate with the OS (Windows Hello).
+/// Blocks until the prompt is answered, so call it from spawn_blocking.
+#[cfg(windows)]
+pub fn verify_user() -> Result<(), String> {
+    let output = crate::steam::hidden_command("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", WINDOWS_HELLO_SCRIPT])
+        .output()
+        .map_err(|e| format!("{}: 无法启动系统验证: {}", OS_AUTH_UNAVAILABLE, e))?;
+    let result = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    match result.as_str() {
+        "Verified" => Ok(()),
+        "DeviceNotPresent" | "NotConfiguredForUser" | "DisabledByPolicy" | "" => {
+            Err(format!("{}: Windows Hello 不可用 ({})", OS_AUTH_UNAVAILABLE, result))
+        }
+        _ => Err(format!("{}: 系统验证未通过 ({})", OS_AUTH_FAILED, result)),
+    }
+}
+
+/// No OS prompt is implemented here (including Touch ID on macOS); callers fall back to the passphrase
+#[cfg(not(windows))]
+pub fn verify_user() -> Result<(), String> {
+    Err(format!("{}: 当前平台不支持系统身份验证", OS_AUTH_UNAVAILABLE))
+}
@@ -0,0 +1,168 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::Manager;
+
+use crate::settings;
+
+/// Favorites autorefresh runs this many times less often while power saving
+pub const POWER_SAVER_REFRESH_FACTOR: u32 = 3;
+
+/// Injected into every page while power saving: freezes CSS animations/transitions and
+/// pauses autoplaying media (media the user starts still plays). Pages hidden behind
+/// other windows also pause their media.
+const POWER_SAVER_JS: &str = r#"
+(function() {
+    window.__xprojPowerSaver = true;
+    if (!document.getElementById('xproj-power-saver')) {
+        var style = document.createElement('style');
+        style.id = 'xproj-power-saver';
+        style.textContent = '*, *::before, *::after { animation-play-state: paused !important; transition: none !important; scroll-behavior: auto !important; }';
+        (document.head || document.documentElement).appendChild(style);
+    }
+    function pauseMedia() {
+        document.querySelectorAll('video[autoplay], audio[autoplay]').forEach(function(m) {
+            m.removeAttribute('autoplay');
+            m.pause();
+        });
+    }
+    pauseMedia();
+    if (!window.__xprojPowerSaverHooked) {
+        window.__xprojPowerSaverHooked = true;
+        document.addEventListener('visibilitychange', function() {
+            if (window.__xprojPowerSaver && document.hidden) {
+                document.querySelectorAll('video, audio').forEach(function(m) { m.pause(); });
+            }
+        });
+    }
+})();
+"#;
+
+/// Undo POWER_SAVER_JS on pages that are already open
+const POWER_SAVER_OFF_JS: &str = r#"
+(function() {
+    window.__xprojPowerSaver = false;
+    var style = document.getElementById('xproj-power-saver');
+    if (style) style.remove();
+})();
+"#;
+
+/// How often the battery state is re-checked while power saving is automatic
+const BATTERY_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
+/// Managed state: whether power saving is currently in effect
+#[derive(Default)]
+pub struct PowerSaver(AtomicBool);
+
+/// Whether power saving is currently in effect
+pub fn is_enabled(app: &tauri::AppHandle) -> bool {
+    app.state::<PowerSaver>().0.load(Ordering::SeqCst)
+}
+
+/// Whether the machine is running on battery (None when it can't be determined).
+/// Blocking (may run a helper process), so call it from spawn_blocking.
+#[cfg(target_os = "linux")]
+pub fn on_battery() -> Option<bool> {
+    let entries = std::fs::read_dir("/sys/class/power_supply").ok()?;
+    let mut found = false;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let read = |name: &str| std::fs::read_to_string(path.join(name)).unwrap_or_default();
+        if read("type").trim() != "Battery" {
+            continue;
+        }
+        found = true;
+        if read("status").trim() == "Discharging" {
+            return Some(true);
+        }
+    }
+    found.then_some(false)
+}
+
+/// Whether the machine is running on battery (None when it can't be determined).
+/// Blocking (may run a helper process), so call it from spawn_blocking.
+#[cfg(windows)]
+pub fn on_battery() -> Option<bool> {
+    // BatteryStatus 1 = discharging; no output means there is no battery
+    let output = crate::steam::hidden_command("powershell")
+        .args([
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "(Get-CimInstance Win32_Battery | Select-Object -First 1).BatteryStatus",
+        ])
+        .output()
+        .ok()?;
+    let status = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if status.is_empty() {
+        return Some(false);
+    }
+    Some(status == "1")
+}
+
+/// Whether the machine is running on battery (None when it can't be determined).
+/// Blocking (may run a helper process), so call it from spawn_blocking.
+#[cfg(target_os = "macos")]
+pub fn on_battery() -> Option<bool> {
+    let output = std::process::Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+    Some(String::from_utf8_lossy(&output.stdout).contains("'Battery Power'"))
+}
+
+/// Battery state isn't detected on this platform
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+pub fn on_battery() -> Option<bool> {
+    None
+}
+
+/// Inject the power saver script into a page if power saving is on (called on page load)
+pub fn apply(window: &tauri::WebviewWindow) {
+    if is_enabled(window.app_handle()) {
+        if let Err(e) = window.eval(POWER_SAVER_JS) {
+            log::warn!("[Power] Failed to inject power saver script: {}", e);
+        }
+    }
+}
+
+/// Work out whether power saving should be on (the saved preference, or the battery state
+/// when no preference is saved) and apply it to the open windows
+pub async fn refresh(app: &tauri::AppHandle) {
+    let enable = match settings::current(app).power_saver {
+        Some(enabled) => enabled,
+        None => tokio::task::spawn_blocking(on_battery)
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or(false),
+    };
+    let was_enabled = app.state::<PowerSaver>().0.swap(enable, Ordering::SeqCst);
+    if was_enabled == enable {
+        return;
+    }
+
+    log::info!("[Power] Power saver {}", if enable { "enabled" } else { "disabled" });
+    let script = if enable { POWER_SAVER_JS } else { POWER_SAVER_OFF_JS };
+    for window in app.webview_windows().values() {
+        if window.label() != "main" {
+            let _ = window.eval(script);
+        }
+    }
+}
+
+/// Apply the power saver state now and keep following the battery state in the background
+pub fn start(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            refresh(&app).await;
+            tokio::time::sleep(BATTERY_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// Turn power saving on or off (None = automatic: on while running on battery).
+/// Freezes page animations/autoplay in the browser windows and slows favorites autorefresh.
+/// Returns whether power saving is now in effect.
+#[tauri::command]
+pub async fn set_power_saver(app: tauri::AppHandle, enabled: Option<bool>) -> Result<bool, String> {
+    settings::update(&app, |s| s.power_saver = enabled)?;
+    refresh(&app).await;
+    Ok(is_enabled(&app))
+}
@@ -0,0 +1,76 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tauri::Manager;
+
+use crate::server_store::unix_now;
+use crate::A2SQueryResult;
+
+/// How many recent failures are kept; older ones are dropped first
+const MAX_RECENT_FAILURES: usize = 200;
+
+/// One failed query, as returned by get_recent_query_failures
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct QueryFailure {
+    pub ip: String,
+    pub port: String,
+    /// error_code when the query set one, otherwise a coarse class of the error message:
+    /// "Dns", "Socket", "NoResponse", "InvalidResponse" or "Other"
+    pub kind: String,
+    pub error: String,
+    /// "udp", "relay" or "tcp"
+    pub query_path: Option<String>,
+    /// Unix timestamp of the failure
+    pub timestamp: u64,
+}
+
+/// Managed state holding the most recent failed queries (in memory only)
+#[derive(Default)]
+pub struct QueryFailures(Mutex<VecDeque<QueryFailure>>);
+
+/// Coarse class of an error message for results without an error_code
+fn classify(error: &str) -> &'static str {
+    if error.starts_with("Failed to resolve") || error.starts_with("No addresses") {
+        "Dns"
+    } else if error.starts_with("Failed to receive") {
+        "NoResponse"
+    } else if error.starts_with("Failed to") {
+        "Socket"
+    } else if error.starts_with("Invalid response")
+        || error.starts_with("Response too short")
+        || error.starts_with("Server rejected")
+    {
+        "InvalidResponse"
+    } else {
+        "Other"
+    }
+}
+
+/// Remember a failed query result; successful results are ignored
+pub fn record(app: &tauri::AppHandle, result: &A2SQueryResult) {
+    if result.success {
+        return;
+    }
+    let error = result.error.clone().unwrap_or_default();
+    let failure = QueryFailure {
+        ip: result.ip.clone(),
+        port: result.port.clone(),
+        kind: result.error_code.clone().unwrap_or_else(|| classify(&error).to_string()),
+        error,
+        query_path: result.query_path.clone(),
+        timestamp: unix_now(),
+    };
+    let failures = app.state::<QueryFailures>();
+    let mut failures = failures.0.lock().unwrap_or_else(|e| e.into_inner());
+    if failures.len() >= MAX_RECENT_FAILURES {
+        failures.pop_front();
+    }
+    failures.push_back(failure);
+}
+
+/// Recent failed queries, oldest first
+#[tauri::command]
+pub async fn get_recent_query_failures(app: tauri::AppHandle) -> Result<Vec<QueryFailure>, String> {
+    let failures = app.state::<QueryFailures>();
+    let failures = failures.0.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(failures.iter().cloned().collect())
+}
@@ -0,0 +1,29 @@
+use std::time::Duration;
+
+use crate::A2SQueryResult;
+
+/// Backend endpoint that performs an A2S_INFO query server-side and returns the result
+const RELAY_QUERY_URL: &str = "https://servers.upkk.com/api/a2s/query";
+
+/// Timeout for a relayed query (covers the backend's own UDP timeout)
+const RELAY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Query a server through the backend relay, for networks where outbound UDP is blocked.
+/// The backend answers with the same A2SQueryResult shape as a local query.
+pub async fn query(ip: &str, port: &str) -> Result<A2SQueryResult, String> {
+    let response = tauri_plugin_http::reqwest::Client::new()
+        .get(RELAY_QUERY_URL)
+        .query(&[("ip", ip), ("port", port)])
+        .timeout(RELAY_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("Relay query failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Relay query failed: HTTP {}", response.status()));
+    }
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read relay response: {}", e))?;
+    serde_json::from_str(&body).map_err(|e| format!("Invalid relay response: {}", e))
+}
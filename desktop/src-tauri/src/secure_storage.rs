@@ -3,11 +3,33 @@ use aes_gcm::{
     Aes256Gcm, Nonce,
 };
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64_STANDARD};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 use rand::RngCore;
 use sha2::{Digest, Sha256};
 use std::fs;
-use std::path::PathBuf;
-use tauri::Manager;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+use tauri::{Emitter, Manager};
+
+/// Plaintext format version: gzip-compressed JSON.
+/// Legacy files hold raw JSON, whose first byte is always '{'.
+const FORMAT_GZIP_JSON: u8 = 0x01;
+
+/// Upper bound on decompressed credential JSON, guards against corrupted input
+const MAX_DECOMPRESSED_SIZE: u64 = 1024 * 1024;
+
+/// Header of salted (v2) files: "v2:" + base64(salt + nonce + ciphertext).
+/// ':' is outside the base64 alphabet, so saltless v1 files can never match it.
+const SALTED_FORMAT_PREFIX: &str = "v2:";
+
+/// Length of the random per-save salt mixed into key derivation
+const SALT_LEN: usize = 16;
+
+/// Error code returned when freshly saved credentials don't read back correctly
+const CREDENTIALS_VERIFY_FAILED: &str = "CredentialsVerifyFailed";
 
 /// Stored credentials structure
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
@@ -27,11 +49,15 @@ pub struct CredentialResponse {
     pub securecode: Option<String>,
 }
 
+/// Path of the persisted fallback device ID (in the user's home directory)
+pub fn fallback_device_id_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".xproj-device-id"))
+}
+
 /// Get or create a persistent fallback device ID
 fn get_or_create_fallback_device_id() -> String {
     // Try to read existing fallback ID from a file in the user's home directory
-    if let Some(home) = dirs::home_dir() {
-        let fallback_path = home.join(".xproj-device-id");
+    if let Some(fallback_path) = fallback_device_id_path() {
         
         // Try to read existing ID
         if let Ok(existing_id) = fs::read_to_string(&fallback_path) {
@@ -58,39 +84,182 @@ fn get_or_create_fallback_device_id() -> String {
     hex::encode(random_bytes)
 }
 
-/// Get machine unique identifier for device binding
-fn get_device_id() -> String {
+/// Result of regenerate_device_id
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct RegeneratedDeviceId {
+    pub device_id: String,
+    /// Whether the fallback ID is the one used for device binding on this machine
+    /// (false when machine_uid is available)
+    pub in_use: bool,
+    pub warning: String,
+}
+
+/// Result of get_key_derivation_status
+#[derive(serde::Serialize, Clone, Debug, Default)]
+pub struct KeyDerivationStatus {
+    /// The device ID is cached and a key derived from it encrypts/decrypts correctly
+    pub ready: bool,
+    /// "machine-uid" or "fallback", None until the device ID has been computed
+    pub source: Option<String>,
+    /// Time taken to compute the device ID and check the key
+    pub duration_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+struct DeviceKeyState {
+    device_id: Option<String>,
+    source: &'static str,
+    status: KeyDerivationStatus,
+}
+
+/// Managed state caching the device ID for the session.
+/// machine_uid can be slow (or even prompt) on some systems, so it is computed once
+/// at startup instead of on every credential operation.
+#[derive(Default)]
+pub struct DeviceKeyCache(Mutex<DeviceKeyState>);
+
+/// Compute the machine unique identifier for device binding, and where it came from
+fn compute_device_id() -> (String, &'static str) {
     match machine_uid::get() {
         Ok(id) => {
             // Hash the machine ID for privacy
             let mut hasher = Sha256::new();
             hasher.update(id.as_bytes());
             let result = hasher.finalize();
-            hex::encode(&result[..16]) // Use first 16 bytes
+            (hex::encode(&result[..16]), "machine-uid") // Use first 16 bytes
         }
         Err(_) => {
             // Fallback: generate and persist a unique device ID
-            get_or_create_fallback_device_id()
+            (get_or_create_fallback_device_id(), "fallback")
         }
     }
 }
 
-/// Derive encryption key from device ID and app secret
-fn derive_key(device_id: &str) -> [u8; 32] {
+/// Check that a key derived from the device ID round-trips through encrypt/decrypt
+fn check_key_round_trip(device_id: &str) -> Result<(), String> {
+    const PROBE: &[u8] = b"xproj-key-check";
+    let encrypted = encrypt_data(PROBE, device_id)?;
+    if decrypt_data(&encrypted, device_id)? != PROBE {
+        return Err("Decrypted data doesn't match".to_string());
+    }
+    Ok(())
+}
+
+/// Compute the device ID, validate key derivation and cache both for the session
+fn prepare_device_key(app: &tauri::AppHandle) -> String {
+    let started = Instant::now();
+    let (device_id, source) = compute_device_id();
+    let error = check_key_round_trip(&device_id).err();
+    if let Some(e) = &error {
+        log::error!("[SecureStorage] Key derivation check failed: {}", e);
+    }
+
+    let cache = app.state::<DeviceKeyCache>();
+    let mut state = cache.0.lock().unwrap_or_else(|e| e.into_inner());
+    state.device_id = Some(device_id.clone());
+    state.source = source;
+    state.status = KeyDerivationStatus {
+        ready: error.is_none(),
+        source: Some(source.to_string()),
+        duration_ms: Some(started.elapsed().as_millis() as u64),
+        error,
+    };
+    device_id
+}
+
+/// Get machine unique identifier for device binding (cached for the session)
+fn get_device_id(app: &tauri::AppHandle) -> String {
+    let cached = app
+        .state::<DeviceKeyCache>()
+        .0
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .device_id
+        .clone();
+    cached.unwrap_or_else(|| prepare_device_key(app))
+}
+
+/// Where the device ID comes from ("machine-uid" or "fallback"), without revealing the ID itself
+pub fn device_id_source(app: &tauri::AppHandle) -> &'static str {
+    get_device_id(app);
+    app.state::<DeviceKeyCache>().0.lock().unwrap_or_else(|e| e.into_inner()).source
+}
+
+/// Drop the cached device ID after the device binding changed (e.g. the fallback ID was
+/// rotated or deleted); it is recomputed on next use
+pub fn invalidate_device_id(app: &tauri::AppHandle) {
+    let cache = app.state::<DeviceKeyCache>();
+    *cache.0.lock().unwrap_or_else(|e| e.into_inner()) = DeviceKeyState::default();
+}
+
+/// Compute and validate the device key in the background at startup so the first
+/// credential operation doesn't pay for it
+pub fn warm_up(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        check_credentials_permissions(&app);
+        prepare_device_key(&app);
+        let status = app.state::<DeviceKeyCache>().0.lock().unwrap_or_else(|e| e.into_inner()).status.clone();
+        log::info!(
+            "[SecureStorage] Device key ready={} source={:?} in {:?} ms",
+            status.ready, status.source, status.duration_ms
+        );
+    });
+}
+
+/// Derive encryption key from device ID, app secret and optional per-save salt
+/// (v1 files have no salt)
+fn derive_key(device_id: &str, salt: Option<&[u8; SALT_LEN]>) -> [u8; 32] {
     let mut hasher = Sha256::new();
     // Combine device ID with app-specific secret
     hasher.update(device_id.as_bytes());
     hasher.update(b"xproj-desktop-secure-v1");
     hasher.update(b"upkk-credential-protection");
+    if let Some(salt) = salt {
+        hasher.update(salt);
+    }
     let result = hasher.finalize();
     let mut key = [0u8; 32];
     key.copy_from_slice(&result);
     key
 }
 
-/// Encrypt data using AES-256-GCM
-fn encrypt_data(data: &str, key: &[u8; 32]) -> Result<String, String> {
-    let cipher = Aes256Gcm::new_from_slice(key)
+/// Compress JSON and prefix it with the format version byte
+fn compress_payload(json: &str) -> Result<Vec<u8>, String> {
+    let mut encoder = GzEncoder::new(vec![FORMAT_GZIP_JSON], Compression::default());
+    encoder
+        .write_all(json.as_bytes())
+        .map_err(|e| format!("Compression failed: {}", e))?;
+    encoder
+        .finish()
+        .map_err(|e| format!("Compression failed: {}", e))
+}
+
+/// Decompress a decrypted payload back to JSON (legacy payloads are plain JSON)
+fn decompress_payload(payload: &[u8]) -> Result<String, String> {
+    match payload.first() {
+        Some(&FORMAT_GZIP_JSON) => {
+            let mut json = String::new();
+            GzDecoder::new(&payload[1..])
+                .take(MAX_DECOMPRESSED_SIZE)
+                .read_to_string(&mut json)
+                .map_err(|e| format!("Decompression failed: {}", e))?;
+            Ok(json)
+        }
+        _ => String::from_utf8(payload.to_vec())
+            .map_err(|e| format!("UTF-8 decode failed: {}", e)),
+    }
+}
+
+/// Encrypt data using AES-256-GCM under a freshly salted device key
+fn encrypt_data(data: &[u8], device_id: &str) -> Result<String, String> {
+    // Random salt per save, so every file is encrypted under a different key
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let key = derive_key(device_id, Some(&salt));
+    
+    let cipher = Aes256Gcm::new_from_slice(&key)
         .map_err(|e| format!("Failed to create cipher: {}", e))?;
     
     // Generate random nonce (12 bytes for AES-GCM)
@@ -100,39 +269,55 @@ fn encrypt_data(data: &str, key: &[u8; 32]) -> Result<String, String> {
     
     // Encrypt
     let ciphertext = cipher
-        .encrypt(nonce, data.as_bytes())
+        .encrypt(nonce, data)
         .map_err(|e| format!("Encryption failed: {}", e))?;
     
-    // Combine nonce + ciphertext and encode as base64
-    let mut combined = nonce_bytes.to_vec();
+    // Combine salt + nonce + ciphertext, encode as base64 behind the format header
+    let mut combined = salt.to_vec();
+    combined.extend_from_slice(&nonce_bytes);
     combined.extend(ciphertext);
-    Ok(BASE64_STANDARD.encode(&combined))
+    Ok(format!("{}{}", SALTED_FORMAT_PREFIX, BASE64_STANDARD.encode(&combined)))
 }
 
-/// Decrypt data using AES-256-GCM
-fn decrypt_data(encrypted: &str, key: &[u8; 32]) -> Result<String, String> {
-    let cipher = Aes256Gcm::new_from_slice(key)
-        .map_err(|e| format!("Failed to create cipher: {}", e))?;
+/// Decrypt data using AES-256-GCM (supports salted v2 and saltless v1 files)
+fn decrypt_data(encrypted: &str, device_id: &str) -> Result<Vec<u8>, String> {
+    let encrypted = encrypted.trim();
     
-    // Decode base64
-    let combined = BASE64_STANDARD.decode(encrypted)
-        .map_err(|e| format!("Base64 decode failed: {}", e))?;
+    // Split off the salt for v2 files; v1 files are base64(nonce + ciphertext)
+    let (salt, combined) = match encrypted.strip_prefix(SALTED_FORMAT_PREFIX) {
+        Some(body) => {
+            let decoded = BASE64_STANDARD.decode(body)
+                .map_err(|e| format!("Base64 decode failed: {}", e))?;
+            if decoded.len() < SALT_LEN {
+                return Err("Invalid encrypted data".to_string());
+            }
+            let mut salt = [0u8; SALT_LEN];
+            salt.copy_from_slice(&decoded[..SALT_LEN]);
+            (Some(salt), decoded[SALT_LEN..].to_vec())
+        }
+        None => {
+            let decoded = BASE64_STANDARD.decode(encrypted)
+                .map_err(|e| format!("Base64 decode failed: {}", e))?;
+            (None, decoded)
+        }
+    };
     
     if combined.len() < 13 {
         return Err("Invalid encrypted data".to_string());
     }
     
+    let key = derive_key(device_id, salt.as_ref());
+    let cipher = Aes256Gcm::new_from_slice(&key)
+        .map_err(|e| format!("Failed to create cipher: {}", e))?;
+    
     // Extract nonce and ciphertext
     let nonce = Nonce::from_slice(&combined[..12]);
     let ciphertext = &combined[12..];
     
     // Decrypt
-    let plaintext = cipher
+    cipher
         .decrypt(nonce, ciphertext)
-        .map_err(|_| "Decryption failed - credentials may be corrupted or from another device".to_string())?;
-    
-    String::from_utf8(plaintext)
-        .map_err(|e| format!("UTF-8 decode failed: {}", e))
+        .map_err(|_| "Decryption failed - credentials may be corrupted or from another device".to_string())
 }
 
 /// Get credentials file path
@@ -149,20 +334,65 @@ fn get_credentials_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
     Ok(app_data_dir.join("credentials.enc"))
 }
 
-/// Save credentials securely (encrypted with device binding)
-#[tauri::command]
-pub async fn save_credentials(
-    app: tauri::AppHandle,
-    steamid64: String,
-    securecode: String,
-) -> Result<CredentialResponse, String> {
-    let device_id = get_device_id();
-    let key = derive_key(&device_id);
+/// Restrict a file to its owner: mode 0600 on Unix, on Windows an ACL that drops inherited
+/// entries and grants only the current user
+fn restrict_to_owner(path: &Path) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("Failed to set file mode: {}", e))?;
+    }
+    #[cfg(windows)]
+    {
+        let user = std::env::var("USERNAME").map_err(|_| "USERNAME is not set".to_string())?;
+        let output = crate::steam::hidden_command("icacls")
+            .arg(path)
+            .args(["/inheritance:r", "/grant:r", &format!("{}:F", user)])
+            .output()
+            .map_err(|e| format!("Failed to run icacls: {}", e))?;
+        if !output.status.success() {
+            return Err(format!("icacls failed: {}", String::from_utf8_lossy(&output.stdout).trim()));
+        }
+    }
+    #[cfg(not(any(unix, windows)))]
+    let _ = path;
+    Ok(())
+}
+
+/// Tighten an existing credentials file that other users can read (written before files
+/// were restricted, or copied in with loose permissions). Blocking; run at startup.
+fn check_credentials_permissions(app: &tauri::AppHandle) {
+    let path = match get_credentials_path(app) {
+        Ok(path) if path.exists() => path,
+        _ => return,
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = match fs::metadata(&path) {
+            Ok(metadata) => metadata.permissions().mode(),
+            Err(_) => return,
+        };
+        if mode & 0o077 == 0 {
+            return;
+        }
+        log::warn!("[SecureStorage] credentials.enc was accessible to other users (mode {:o}), tightening", mode & 0o777);
+    }
+    // Reading the ACL back needs the Win32 security API, so Windows re-applies it every start
+    if let Err(e) = restrict_to_owner(&path) {
+        log::warn!("[SecureStorage] Failed to restrict credentials.enc: {}", e);
+    }
+}
+
+/// Encrypt credentials under this device's key, write them and read them back
+fn write_credentials(app: &tauri::AppHandle, steamid64: String, securecode: String) -> Result<(), String> {
+    let device_id = get_device_id(app);
     
     // Create credentials object
     let credentials = StoredCredentials {
-        steamid64: steamid64.clone(),
-        securecode: securecode.clone(),
+        steamid64,
+        securecode,
         device_id: device_id.clone(),
         created_at: std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -170,20 +400,60 @@ pub async fn save_credentials(
             .as_secs(),
     };
     
+    let path = get_credentials_path(app)?;
+    store_credentials(&path, &credentials)
+}
+
+/// Compress and encrypt credentials into the file, then read it back so a key/format bug
+/// fails the save instead of the next load
+fn store_credentials(path: &Path, credentials: &StoredCredentials) -> Result<(), String> {
     // Serialize to JSON
-    let json = serde_json::to_string(&credentials)
+    let json = serde_json::to_string(credentials)
         .map_err(|e| format!("Serialization failed: {}", e))?;
     
-    // Encrypt
-    let encrypted = encrypt_data(&json, &key)?;
+    // Compress and encrypt
+    let payload = compress_payload(&json)?;
+    let encrypted = encrypt_data(&payload, &credentials.device_id)?;
     
-    // Save to file
-    let path = get_credentials_path(&app)?;
-    fs::write(&path, encrypted)
+    // Save to file, created owner-only on Unix so it is never readable by others in between
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(encrypted.as_bytes()))
         .map_err(|e| format!("Failed to save credentials: {}", e))?;
+    // The mode only applies to a new file; an existing one (and the Windows ACL) is fixed up here
+    if let Err(e) = restrict_to_owner(path) {
+        log::warn!("[SecureStorage] Failed to restrict credentials.enc: {}", e);
+    }
     
+    if let Err(e) = verify_saved_credentials(path, credentials) {
+        log::error!("[SecureStorage] Saved credentials failed verification: {}", e);
+        let _ = fs::remove_file(path);
+        return Err(format!("{}: 凭据保存校验失败: {}", CREDENTIALS_VERIFY_FAILED, e));
+    }
+    Ok(())
+}
+
+/// Save credentials securely (encrypted with device binding)
+#[tauri::command]
+pub async fn save_credentials(
+    app: tauri::AppHandle,
+    steamid64: String,
+    securecode: String,
+) -> Result<CredentialResponse, String> {
+    write_credentials(&app, steamid64.clone(), securecode)?;
     log::info!("[SecureStorage] Credentials saved successfully with device binding");
     
+    // Remember this account for auto-login on next startup
+    let last_account = steamid64.clone();
+    crate::settings::update(&app, |s| s.last_active_steamid64 = Some(last_account))?;
+    
     Ok(CredentialResponse {
         success: true,
         message: "凭据已安全保存".to_string(),
@@ -192,30 +462,27 @@ pub async fn save_credentials(
     })
 }
 
-/// Load credentials securely (verify device binding)
-#[tauri::command]
-pub async fn load_credentials(app: tauri::AppHandle) -> Result<CredentialResponse, String> {
-    let device_id = get_device_id();
-    let key = derive_key(&device_id);
-    
-    let path = get_credentials_path(&app)?;
-    
+/// Read and decrypt stored credentials (verify device binding).
+/// Returns None if no credentials file exists.
+fn read_credentials(app: &tauri::AppHandle) -> Result<Option<StoredCredentials>, String> {
+    let path = get_credentials_path(app)?;
+    read_credentials_file(&path, &get_device_id(app))
+}
+
+/// Decrypt a credentials file bound to device_id (None if the file doesn't exist)
+fn read_credentials_file(path: &Path, device_id: &str) -> Result<Option<StoredCredentials>, String> {
     // Check if file exists
     if !path.exists() {
-        return Ok(CredentialResponse {
-            success: false,
-            message: "未找到保存的凭据".to_string(),
-            steamid64: None,
-            securecode: None,
-        });
+        return Ok(None);
     }
     
     // Read encrypted data
-    let encrypted = fs::read_to_string(&path)
+    let encrypted = fs::read_to_string(path)
         .map_err(|e| format!("Failed to read credentials: {}", e))?;
     
-    // Decrypt
-    let json = decrypt_data(&encrypted, &key)?;
+    // Decrypt and decompress
+    let payload = decrypt_data(&encrypted, device_id)?;
+    let json = decompress_payload(&payload)?;
     
     // Deserialize
     let credentials: StoredCredentials = serde_json::from_str(&json)
@@ -227,8 +494,94 @@ pub async fn load_credentials(app: tauri::AppHandle) -> Result<CredentialRespons
         return Err("凭据与当前设备不匹配，可能已被复制。请重新登录。".to_string());
     }
     
+    // Small clock skew is tolerated; a timestamp far in the future means the clock was wrong when saving
+    if crate::clock::is_future_timestamp(credentials.created_at) {
+        log::warn!("[SecureStorage] Credentials created_at is in the future, system clock may be skewed");
+    }
+    
+    Ok(Some(credentials))
+}
+
+/// Whether usable credentials (readable on this device) are saved
+pub fn has_saved_credentials(app: &tauri::AppHandle) -> bool {
+    matches!(read_credentials(app), Ok(Some(_)))
+}
+
+/// Decrypted credentials for a portable backup (None if nothing is saved)
+pub fn export_for_backup(app: &tauri::AppHandle) -> Result<Option<StoredCredentials>, String> {
+    read_credentials(app)
+}
+
+/// Restore credentials from a backup, re-binding them to this device
+pub fn import_from_backup(app: &tauri::AppHandle, credentials: StoredCredentials) -> Result<(), String> {
+    write_credentials(app, credentials.steamid64, credentials.securecode)?;
+    log::info!("[SecureStorage] Credentials restored from backup and bound to this device");
+    Ok(())
+}
+
+/// Check that the credentials file decrypts (with device binding) to exactly what was saved
+fn verify_saved_credentials(path: &Path, expected: &StoredCredentials) -> Result<(), String> {
+    let stored = read_credentials_file(path, &expected.device_id)?.ok_or_else(|| "credentials file is missing".to_string())?;
+    if stored.steamid64 != expected.steamid64
+        || stored.securecode != expected.securecode
+        || stored.device_id != expected.device_id
+        || stored.created_at != expected.created_at
+    {
+        return Err("stored fields don't match".to_string());
+    }
+    Ok(())
+}
+
+/// Load credentials securely (verify device binding)
+#[tauri::command]
+pub async fn load_credentials(app: tauri::AppHandle) -> Result<CredentialResponse, String> {
+    let credentials = match read_credentials(&app)? {
+        Some(c) => c,
+        None => {
+            return Ok(CredentialResponse {
+                success: false,
+                message: "未找到保存的凭据".to_string(),
+                steamid64: None,
+                securecode: None,
+            });
+        }
+    };
+    
     log::info!("[SecureStorage] Credentials loaded successfully");
     
+    Ok(CredentialResponse {
+        success: true,
+        message: "凭据加载成功".to_string(),
+        steamid64: Some(credentials.steamid64),
+        securecode: ungated_securecode(&app, credentials.securecode),
+    })
+}
+
+/// Return the securecode unless the OS authentication gate is enabled,
+/// in which case it is only available through get_securecode_authenticated
+fn ungated_securecode(app: &tauri::AppHandle, securecode: String) -> Option<String> {
+    if crate::settings::current(app).require_os_auth_for_securecode {
+        None
+    } else {
+        Some(securecode)
+    }
+}
+
+/// Return the stored credentials including the securecode after a fresh OS authentication prompt.
+/// Where there is no OS prompt, the passphrase set when the gate was enabled is required instead.
+#[tauri::command]
+pub async fn get_securecode_authenticated(
+    app: tauri::AppHandle,
+    passphrase: Option<String>,
+) -> Result<CredentialResponse, String> {
+    let app_auth = app.clone();
+    tokio::task::spawn_blocking(move || crate::os_auth::authenticate(&app_auth, passphrase.as_deref()))
+        .await
+        .map_err(|e| format!("Authentication task failed: {}", e))??;
+    
+    let credentials = read_credentials(&app)?.ok_or_else(|| "未找到保存的凭据".to_string())?;
+    log::info!("[SecureStorage] Securecode released after OS authentication");
+    
     Ok(CredentialResponse {
         success: true,
         message: "凭据加载成功".to_string(),
@@ -237,6 +590,91 @@ pub async fn load_credentials(app: tauri::AppHandle) -> Result<CredentialRespons
     })
 }
 
+/// Enable or disable requiring OS authentication before the securecode is returned.
+/// Both directions need a fresh authentication, so the gate can't be switched off unnoticed.
+/// Where there is no OS prompt (or it isn't set up), enabling sets `passphrase` as the fallback
+/// and disabling requires it.
+#[tauri::command]
+pub async fn set_securecode_os_auth(
+    app: tauri::AppHandle,
+    enabled: bool,
+    passphrase: Option<String>,
+) -> Result<(), String> {
+    if crate::settings::current(&app).require_os_auth_for_securecode == enabled {
+        return Ok(());
+    }
+    let app_auth = app.clone();
+    tokio::task::spawn_blocking(move || -> Result<(), String> {
+        if !enabled {
+            crate::os_auth::authenticate(&app_auth, passphrase.as_deref())?;
+            return crate::os_auth::save_verifier(&app_auth, None);
+        }
+        // Enabling checks the prompt works first so the user can't lock themselves out
+        match crate::os_auth::verify_user() {
+            Ok(()) => crate::os_auth::save_verifier(&app_auth, None),
+            Err(e) if crate::os_auth::is_unavailable(&e) => {
+                let passphrase = passphrase.filter(|p| !p.is_empty()).ok_or_else(|| {
+                    format!("{}: 当前平台不支持系统身份验证，请设置凭据保护密码", crate::os_auth::PASSPHRASE_REQUIRED)
+                })?;
+                let verifier = crate::os_auth::new_passphrase_verifier(&passphrase)?;
+                crate::os_auth::save_verifier(&app_auth, Some(&verifier))
+            }
+            Err(e) => Err(e),
+        }
+    })
+    .await
+    .map_err(|e| format!("Authentication task failed: {}", e))??;
+    crate::settings::update(&app, |s| s.require_os_auth_for_securecode = enabled)?;
+    log::info!("[SecureStorage] Securecode OS authentication gate {}", if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+/// Managed flag: auto-login already ran this launch (the main window reloads on navigation)
+#[derive(Default)]
+pub struct AutoLoginDone(AtomicBool);
+
+/// Auto-login on startup: load the last active account's credentials and emit
+/// credentials-ready to the main window only, so the UI can skip the login screen.
+/// Runs once per launch.
+pub fn auto_login(app: &tauri::AppHandle) {
+    if app.state::<AutoLoginDone>().0.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let last_account = match crate::settings::current(app).last_active_steamid64 {
+        Some(id) => id,
+        None => return,
+    };
+    
+    match read_credentials(app) {
+        Ok(Some(credentials)) if credentials.steamid64 == last_account => {
+            log::info!("[SecureStorage] Auto-login succeeded for last active account");
+            let _ = app.emit_to("main", "credentials-ready", CredentialResponse {
+                success: true,
+                message: "凭据加载成功".to_string(),
+                steamid64: Some(credentials.steamid64),
+                securecode: ungated_securecode(app, credentials.securecode),
+            });
+        }
+        Ok(_) => log::info!("[SecureStorage] No saved credentials for last active account"),
+        Err(e) => log::warn!("[SecureStorage] Auto-login failed: {}", e),
+    }
+}
+
+/// Get the last active account (steamid64) used for auto-login
+#[tauri::command]
+pub async fn get_last_account(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    Ok(crate::settings::current(&app).last_active_steamid64)
+}
+
+/// Set the last active account (empty string clears it)
+#[tauri::command]
+pub async fn set_last_account(app: tauri::AppHandle, steamid64: String) -> Result<(), String> {
+    let steamid64 = steamid64.trim().to_string();
+    let last_account = if steamid64.is_empty() { None } else { Some(steamid64) };
+    crate::settings::update(&app, |s| s.last_active_steamid64 = last_account)?;
+    Ok(())
+}
+
 /// Clear stored credentials
 #[tauri::command]
 pub async fn clear_credentials(app: tauri::AppHandle) -> Result<CredentialResponse, String> {
@@ -248,6 +686,9 @@ pub async fn clear_credentials(app: tauri::AppHandle) -> Result<CredentialRespon
         log::info!("[SecureStorage] Credentials cleared");
     }
     
+    // Nothing left to auto-login with
+    crate::settings::update(&app, |s| s.last_active_steamid64 = None)?;
+    
     Ok(CredentialResponse {
         success: true,
         message: "凭据已清除".to_string(),
@@ -258,8 +699,42 @@ pub async fn clear_credentials(app: tauri::AppHandle) -> Result<CredentialRespon
 
 /// Get current device ID (for display/debugging)
 #[tauri::command]
-pub async fn get_device_fingerprint() -> Result<String, String> {
-    Ok(get_device_id())
+pub async fn get_device_fingerprint(app: tauri::AppHandle) -> Result<String, String> {
+    Ok(get_device_id(&app))
+}
+
+/// Whether the device key was computed and validated (at startup or on first use),
+/// where the device ID came from and how long it took
+#[tauri::command]
+pub async fn get_key_derivation_status(app: tauri::AppHandle) -> Result<KeyDerivationStatus, String> {
+    let cache = app.state::<DeviceKeyCache>();
+    let status = cache.0.lock().unwrap_or_else(|e| e.into_inner()).status.clone();
+    Ok(status)
+}
+
+/// Delete the fallback device ID file and generate a new one.
+/// Only the fallback ID is rotated; the machine_uid-derived ID can't be changed.
+#[tauri::command]
+pub async fn regenerate_device_id(app: tauri::AppHandle) -> Result<RegeneratedDeviceId, String> {
+    let path = fallback_device_id_path().ok_or_else(|| "无法确定用户主目录".to_string())?;
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to delete device ID file: {}", e))?;
+    }
+
+    let device_id = get_or_create_fallback_device_id();
+    invalidate_device_id(&app);
+    if !path.exists() {
+        return Err("Failed to save new device ID".to_string());
+    }
+    let in_use = device_id_source(&app) == "fallback";
+    log::info!("[SecureStorage] Fallback device ID regenerated (in use: {})", in_use);
+
+    let warning = if in_use {
+        "设备标识已更换，之前保存的凭据将无法解密，需要重新登录".to_string()
+    } else {
+        "已重新生成备用设备标识，但当前设备使用硬件标识绑定，已保存的凭据不受影响".to_string()
+    };
+    Ok(RegeneratedDeviceId { device_id, in_use, warning })
 }
 
 /// Check if credentials exist
@@ -268,3 +743,153 @@ pub async fn has_stored_credentials(app: tauri::AppHandle) -> Result<bool, Strin
     let path = get_credentials_path(&app)?;
     Ok(path.exists())
 }
+
+/// Result of validate_stored_credentials (never includes the securecode)
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct CredentialValidation {
+    pub valid: bool,
+    pub steamid64: Option<String>,
+    pub reason: Option<String>,
+}
+
+/// Check that stored credentials decrypt and are bound to this device,
+/// distinguishing "exists" from "exists but unreadable" without returning secrets
+#[tauri::command]
+pub async fn validate_stored_credentials(app: tauri::AppHandle) -> Result<CredentialValidation, String> {
+    Ok(match read_credentials(&app) {
+        Ok(Some(credentials)) => CredentialValidation {
+            valid: true,
+            steamid64: Some(credentials.steamid64),
+            reason: None,
+        },
+        Ok(None) => CredentialValidation {
+            valid: false,
+            steamid64: None,
+            reason: Some("未找到保存的凭据".to_string()),
+        },
+        Err(e) => CredentialValidation {
+            valid: false,
+            steamid64: None,
+            reason: Some(e),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEVICE_ID: &str = "0123456789abcdef0123456789abcdef";
+
+    fn sample_credentials() -> StoredCredentials {
+        StoredCredentials {
+            steamid64: "76561198000000000".to_string(),
+            securecode: "secure-code".to_string(),
+            device_id: DEVICE_ID.to_string(),
+            created_at: 1_700_000_000,
+        }
+    }
+
+    fn sample_json() -> String {
+        serde_json::to_string(&sample_credentials()).unwrap()
+    }
+
+    // Fresh directory under the system temp dir, unique per test
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("secure_storage_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn gzip_payload_round_trips() {
+        let json = sample_json();
+        let payload = compress_payload(&json).unwrap();
+        assert_eq!(payload[0], FORMAT_GZIP_JSON);
+        assert_eq!(decompress_payload(&payload).unwrap(), json);
+
+        let encrypted = encrypt_data(&payload, DEVICE_ID).unwrap();
+        let decrypted = decrypt_data(&encrypted, DEVICE_ID).unwrap();
+        assert_eq!(decompress_payload(&decrypted).unwrap(), json);
+    }
+
+    #[test]
+    fn legacy_json_payload_still_decodes() {
+        let json = sample_json();
+        assert_eq!(decompress_payload(json.as_bytes()).unwrap(), json);
+
+        let encrypted = encrypt_data(json.as_bytes(), DEVICE_ID).unwrap();
+        let decrypted = decrypt_data(&encrypted, DEVICE_ID).unwrap();
+        assert_eq!(decompress_payload(&decrypted).unwrap(), json);
+    }
+
+    #[test]
+    fn store_credentials_round_trips() {
+        let dir = temp_dir("round_trip");
+        let path = dir.join("credentials.enc");
+        store_credentials(&path, &sample_credentials()).unwrap();
+
+        let stored = read_credentials_file(&path, DEVICE_ID).unwrap().unwrap();
+        assert_eq!(stored.securecode, "secure-code");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn save_fails_when_the_file_cannot_be_written() {
+        let dir = temp_dir("unwritable");
+        // The parent of the target is a regular file, so the write itself fails
+        let blocker = dir.join("not_a_dir");
+        fs::write(&blocker, b"").unwrap();
+        assert!(store_credentials(&blocker.join("credentials.enc"), &sample_credentials()).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn verification_rejects_a_corrupted_file() {
+        let dir = temp_dir("corrupted");
+        let path = dir.join("credentials.enc");
+        let credentials = sample_credentials();
+        store_credentials(&path, &credentials).unwrap();
+
+        // Flip one byte of the stored ciphertext: decryption (and so the save check) must fail
+        let mut data = fs::read(&path).unwrap();
+        let last = data.len() - 1;
+        data[last] = if data[last] == b'A' { b'B' } else { b'A' };
+        fs::write(&path, data).unwrap();
+        assert!(verify_saved_credentials(&path, &credentials).is_err());
+
+        // Valid ciphertext holding different fields fails too
+        let other = StoredCredentials { securecode: "other".to_string(), ..sample_credentials() };
+        store_credentials(&path, &other).unwrap();
+        assert!(verify_saved_credentials(&path, &credentials).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn restrict_to_owner_clears_group_and_other_bits() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = temp_dir("restrict");
+        let path = dir.join("credentials.enc");
+        fs::write(&path, b"data").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        restrict_to_owner(&path).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stored_credentials_are_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = temp_dir("owner_only");
+        let path = dir.join("credentials.enc");
+        store_credentials(&path, &sample_credentials()).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,390 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Manager;
+
+use crate::server_store::{server_key, unix_now};
+use crate::A2SQueryResult;
+
+/// Backend endpoint returning the community server list
+const SERVER_LIST_URL: &str = "https://servers.upkk.com/api/servers";
+
+/// How long a fetched page is reused before asking the backend again (seconds)
+const SERVER_LIST_CACHE_TTL_SECS: u64 = 30;
+
+/// Largest page size accepted by fetch_server_list
+const MAX_PAGE_SIZE: u32 = 100;
+
+/// A server as listed by the backend. Player counts may be stale; see merge_server_data.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct BackendServer {
+    pub ip: String,
+    #[serde(deserialize_with = "port_as_string")]
+    pub port: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub map_name: String,
+    #[serde(default)]
+    pub players: i32,
+    #[serde(default)]
+    pub max_players: i32,
+    #[serde(default)]
+    pub region: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// One page of the backend server list with pagination metadata
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct ServerListPage {
+    pub servers: Vec<BackendServer>,
+    pub page: u32,
+    pub page_size: u32,
+    pub total: u64,
+    #[serde(default)]
+    pub total_pages: u32,
+    /// Served from the local cache rather than a fresh request
+    #[serde(default)]
+    pub cached: bool,
+}
+
+/// Server entry combining backend metadata with a live A2S result
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct MergedServer {
+    pub ip: String,
+    pub port: String,
+    pub name: String,
+    pub map_name: String,
+    pub players: i32,
+    pub max_players: i32,
+    /// Live round-trip time, None without a successful live query
+    pub ping_ms: Option<u32>,
+    /// Result of the live query, None when the server wasn't queried
+    pub online: Option<bool>,
+    pub region: Option<String>,
+    pub tags: Vec<String>,
+    pub description: Option<String>,
+    /// Where the entry came from: "both", "backend" or "live"
+    pub source: &'static str,
+}
+
+/// Managed state caching recently fetched pages, keyed by request parameters
+#[derive(Default)]
+pub struct ServerListCache(Mutex<HashMap<String, (ServerListPage, u64)>>);
+
+/// The backend sends ports as numbers; the rest of the app uses strings
+fn port_as_string<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(s) => Ok(s),
+        serde_json::Value::Number(n) => Ok(n.to_string()),
+        other => Err(serde::de::Error::custom(format!("invalid port: {}", other))),
+    }
+}
+
+fn cache_key(page: u32, page_size: u32, filter: Option<&str>) -> String {
+    format!("{}:{}:{}", page, page_size, filter.unwrap_or(""))
+}
+
+/// Cached page for these parameters; stale entries are only returned when allow_stale is set
+fn cached_page(app: &tauri::AppHandle, key: &str, allow_stale: bool) -> Option<ServerListPage> {
+    let cache = app.state::<ServerListCache>();
+    let entries = cache.0.lock().unwrap_or_else(|e| e.into_inner());
+    let (page, fetched_at) = entries.get(key)?;
+    if allow_stale || unix_now().saturating_sub(*fetched_at) < SERVER_LIST_CACHE_TTL_SECS {
+        Some(ServerListPage { cached: true, ..page.clone() })
+    } else {
+        None
+    }
+}
+
+/// Drop all cached pages
+pub fn clear_cache(app: &tauri::AppHandle) {
+    let cache = app.state::<ServerListCache>();
+    cache.0.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// Fetch one page of the server list from the backend (page starts at 1).
+/// filter is a free-text search passed through to the backend.
+/// Pages are cached briefly; while the network is paused only cached pages are returned.
+#[tauri::command]
+pub async fn fetch_server_list(
+    app: tauri::AppHandle,
+    page: u32,
+    page_size: u32,
+    filter: Option<String>,
+) -> Result<ServerListPage, String> {
+    if page == 0 {
+        return Err("page starts at 1".to_string());
+    }
+    if page_size == 0 || page_size > MAX_PAGE_SIZE {
+        return Err(format!("page_size must be between 1 and {}", MAX_PAGE_SIZE));
+    }
+    let filter = filter.map(|f| f.trim().to_string()).filter(|f| !f.is_empty());
+    let key = cache_key(page, page_size, filter.as_deref());
+
+    if crate::network_paused(&app) {
+        return cached_page(&app, &key, true)
+            .ok_or_else(|| format!("{}: 网络活动已暂停", crate::NETWORK_PAUSED));
+    }
+    if let Some(cached) = cached_page(&app, &key, false) {
+        return Ok(cached);
+    }
+
+    let mut query = vec![("page", page.to_string()), ("page_size", page_size.to_string())];
+    if let Some(filter) = &filter {
+        query.push(("filter", filter.clone()));
+    }
+    let response = tauri_plugin_http::reqwest::Client::new()
+        .get(SERVER_LIST_URL)
+        .query(&query)
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch server list: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to fetch server list: HTTP {}", response.status()));
+    }
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read server list: {}", e))?;
+    let mut list: ServerListPage = serde_json::from_str(&body)
+        .map_err(|e| format!("Invalid server list response: {}", e))?;
+
+    if list.total_pages == 0 && list.page_size > 0 {
+        list.total_pages = list.total.div_ceil(list.page_size as u64) as u32;
+    }
+    list.cached = false;
+
+    let cache = app.state::<ServerListCache>();
+    cache
+        .0
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(key, (list.clone(), unix_now()));
+    Ok(list)
+}
+
+/// Join backend list entries with live A2S results by ip:port.
+/// Live counts, map and ping win when the live query succeeded; backend metadata fills in the rest.
+/// Backend order is kept, servers only seen live are appended in their original order.
+#[tauri::command]
+pub async fn merge_server_data(
+    backend_servers: Vec<BackendServer>,
+    live_results: Vec<A2SQueryResult>,
+) -> Result<Vec<MergedServer>, String> {
+    let mut live: HashMap<String, A2SQueryResult> = HashMap::new();
+    let mut live_order = Vec::new();
+    for result in live_results {
+        let key = server_key(&result.ip, &result.port);
+        if live.insert(key.clone(), result).is_none() {
+            live_order.push(key);
+        }
+    }
+
+    let mut merged = Vec::with_capacity(backend_servers.len() + live.len());
+    for server in backend_servers {
+        let key = server_key(&server.ip, &server.port);
+        let entry = match live.remove(&key) {
+            Some(result) if result.success => MergedServer {
+                name: if server.name.is_empty() { result.name } else { server.name },
+                map_name: result.map_name,
+                players: result.players,
+                max_players: result.max_players,
+                ping_ms: Some(result.ping_ms),
+                online: Some(true),
+                source: "both",
+                ip: server.ip,
+                port: server.port,
+                region: server.region,
+                tags: server.tags,
+                description: server.description,
+            },
+            // Failed live query: the server is down, keep the backend figures for display
+            Some(_) => MergedServer { online: Some(false), source: "both", ..from_backend(server) },
+            None => from_backend(server),
+        };
+        merged.push(entry);
+    }
+
+    for key in live_order {
+        if let Some(result) = live.remove(&key) {
+            merged.push(MergedServer {
+                online: Some(result.success),
+                ping_ms: result.success.then_some(result.ping_ms),
+                ip: result.ip,
+                port: result.port,
+                name: result.name,
+                map_name: result.map_name,
+                players: result.players,
+                max_players: result.max_players,
+                region: None,
+                tags: Vec::new(),
+                description: None,
+                source: "live",
+            });
+        }
+    }
+    Ok(merged)
+}
+
+/// Merged entry for a backend server without a live result
+fn from_backend(server: BackendServer) -> MergedServer {
+    MergedServer {
+        ip: server.ip,
+        port: server.port,
+        name: server.name,
+        map_name: server.map_name,
+        players: server.players,
+        max_players: server.max_players,
+        ping_ms: None,
+        online: None,
+        region: server.region,
+        tags: server.tags,
+        description: server.description,
+        source: "backend",
+    }
+}
+
+/// Servers sharing one host IP, as returned by group_servers_by_host
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct HostGroup {
+    pub ip: String,
+    pub server_count: usize,
+    pub online_count: usize,
+    /// Human players summed over the host's servers that answered
+    pub real_players: i32,
+    /// The server shown for the collapsed group: online first, then most players, then lowest port
+    pub representative: A2SQueryResult,
+    /// The host's other servers, in their original order
+    pub others: Vec<A2SQueryResult>,
+}
+
+/// Ordering key for picking a group's representative (smaller is better)
+fn representative_rank(result: &A2SQueryResult) -> (bool, i32, u32) {
+    (
+        !result.success,
+        -result.real_players,
+        result.port.parse().unwrap_or(u32::MAX),
+    )
+}
+
+/// Group query results by host IP so hosts running many servers can be shown collapsed.
+/// Groups keep the order in which their IPs first appear; single-server hosts form groups of one.
+#[tauri::command]
+pub async fn group_servers_by_host(results: Vec<A2SQueryResult>) -> Result<Vec<HostGroup>, String> {
+    let mut by_host: HashMap<String, Vec<A2SQueryResult>> = HashMap::new();
+    let mut host_order = Vec::new();
+    for result in results {
+        let host = result.ip.trim().to_ascii_lowercase();
+        by_host
+            .entry(host.clone())
+            .or_insert_with(|| {
+                host_order.push(host);
+                Vec::new()
+            })
+            .push(result);
+    }
+
+    let groups = host_order
+        .into_iter()
+        .filter_map(|host| {
+            let mut servers = by_host.remove(&host)?;
+            let best = servers
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, result)| representative_rank(result))
+                .map(|(index, _)| index)?;
+            let online: Vec<&A2SQueryResult> = servers.iter().filter(|r| r.success).collect();
+            let online_count = online.len();
+            let real_players = online.iter().map(|r| r.real_players).sum();
+            let representative = servers.remove(best);
+            Some(HostGroup {
+                ip: representative.ip.clone(),
+                server_count: servers.len() + 1,
+                online_count,
+                real_players,
+                representative,
+                others: servers,
+            })
+        })
+        .collect();
+    Ok(groups)
+}
+
+/// Map name for matching: lowercased, with any workshop/<id>/ or directory prefix dropped
+fn normalize_map_name(map_name: &str) -> String {
+    let map_name = map_name.trim().to_lowercase();
+    match map_name.rsplit_once('/') {
+        Some((_, name)) => name.to_string(),
+        None => map_name,
+    }
+}
+
+/// Glob match where '*' matches any run of characters and '?' exactly one
+fn glob_matches(pattern: &[char], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // Position after the last '*' and the text position it was tried against
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_t)) => {
+                    backtrack = Some((star_p, star_t + 1));
+                    p = star_p;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Whether a map name matches the pattern: a glob when it contains '*' or '?', otherwise a substring
+fn map_matches(pattern: &str, map_name: &str) -> bool {
+    let map_name = normalize_map_name(map_name);
+    if pattern.contains(['*', '?']) {
+        let pattern: Vec<char> = pattern.chars().collect();
+        let map_name: Vec<char> = map_name.chars().collect();
+        glob_matches(&pattern, &map_name)
+    } else {
+        map_name.contains(pattern)
+    }
+}
+
+/// Batch-query "ip:port" entries (like query_servers_a2s) and keep the servers that answered
+/// with a map matching map_pattern, lowest ping first. Empty when nothing matches.
+#[tauri::command]
+pub async fn find_servers_by_map(
+    app: tauri::AppHandle,
+    servers: Vec<String>,
+    map_pattern: String,
+) -> Result<Vec<A2SQueryResult>, String> {
+    let pattern = map_pattern.trim().to_lowercase();
+    if pattern.is_empty() {
+        return Err("地图名称不能为空".to_string());
+    }
+    let mut matches: Vec<A2SQueryResult> = crate::query_batch(app, servers, Some(false), None, None)
+        .await?
+        .into_iter()
+        .filter(|result| result.success && map_matches(&pattern, &result.map_name))
+        .collect();
+    matches.sort_by_key(|result| result.ping_ms);
+    Ok(matches)
+}
@@ -0,0 +1,381 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::Manager;
+
+/// Maximum query samples kept per server (oldest are dropped first)
+const MAX_SAMPLES_PER_SERVER: usize = 2000;
+
+/// Samples older than this are pruned; longer than any uptime or ping window the UI asks for.
+/// Servers left without samples (and without overrides) are dropped from the store.
+const SAMPLE_RETENTION_SECS: u64 = 30 * 24 * 3600;
+
+/// Minimum samples in the window before uptime is reported
+const MIN_UPTIME_SAMPLES: usize = 3;
+
+/// Minimum successful samples in the window before an average ping is reported
+const MIN_PING_SAMPLES: usize = 3;
+
+/// One recorded A2S query outcome
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct QuerySample {
+    /// Unix timestamp (seconds)
+    pub timestamp: u64,
+    pub online: bool,
+    pub players: i32,
+    /// Round-trip time of successful queries (older samples have none)
+    #[serde(default)]
+    pub ping_ms: Option<u32>,
+}
+
+/// Allowed range for a per-server query timeout (milliseconds)
+const OVERRIDE_TIMEOUT_RANGE_MS: std::ops::RangeInclusive<u64> = 100..=30_000;
+
+/// Per-server A2S query settings that take precedence over the global defaults
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ServerOverrides {
+    pub timeout_ms: Option<u64>,
+    /// Query port when it differs from the game port
+    pub query_port: Option<u16>,
+    pub source_ip: Option<String>,
+}
+
+impl ServerOverrides {
+    /// Nothing overridden, so the record doesn't need to be kept for the overrides' sake
+    fn is_empty(&self) -> bool {
+        self.timeout_ms.is_none() && self.query_port.is_none() && self.source_ip.is_none()
+    }
+}
+
+/// Everything we remember about a single server (keyed by ip:port)
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ServerRecord {
+    pub samples: VecDeque<QuerySample>,
+    pub overrides: ServerOverrides,
+    /// Unix timestamp of the first successful query
+    pub first_seen: Option<u64>,
+}
+
+/// Managed state holding per-server records, persisted to server_store.json
+#[derive(Default)]
+pub struct ServerStore {
+    records: Mutex<HashMap<String, ServerRecord>>,
+    dirty: AtomicBool,
+}
+
+/// Uptime over a time window, returned by get_server_uptime
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ServerUptime {
+    pub uptime_percent: f64,
+    pub sample_count: usize,
+}
+
+/// Average ping over a time window, returned by get_average_ping
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct AveragePing {
+    pub average_ping_ms: f64,
+    pub sample_count: usize,
+}
+
+/// How long a server has been known, returned by get_server_age
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ServerAge {
+    pub first_seen: u64,
+    pub days_known: f64,
+}
+
+/// Current Unix time in seconds
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Key used to identify a server in the store
+pub fn server_key(ip: &str, port: &str) -> String {
+    format!("{}:{}", ip.trim(), port.trim())
+}
+
+/// Get store file path
+fn get_store_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+
+    // Ensure directory exists
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+
+    Ok(app_data_dir.join("server_store.json"))
+}
+
+/// Load the store from disk (empty if missing or unreadable)
+pub fn load(app: &tauri::AppHandle) -> ServerStore {
+    let mut records: HashMap<String, ServerRecord> = get_store_path(app)
+        .ok()
+        .filter(|path| path.exists())
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| match serde_json::from_str(&json) {
+            Ok(records) => Some(records),
+            Err(e) => {
+                log::warn!("[ServerStore] Failed to parse server store, starting empty: {}", e);
+                None
+            }
+        })
+        .unwrap_or_default();
+    // Stores written before pruning existed can be large; the pruned copy is saved on the next flush
+    prune(&mut records, unix_now());
+
+    ServerStore {
+        records: Mutex::new(records),
+        dirty: AtomicBool::new(false),
+    }
+}
+
+/// Drop a record's samples that are past the retention period
+fn prune_samples(record: &mut ServerRecord, cutoff: u64) {
+    while record.samples.front().is_some_and(|s| s.timestamp < cutoff) {
+        record.samples.pop_front();
+    }
+}
+
+/// Prune old samples and forget servers that haven't been queried within the retention period
+fn prune(records: &mut HashMap<String, ServerRecord>, now: u64) {
+    let cutoff = now.saturating_sub(SAMPLE_RETENTION_SECS);
+    records.retain(|_, record| {
+        prune_samples(record, cutoff);
+        !record.samples.is_empty() || !record.overrides.is_empty()
+    });
+}
+
+/// Write the store to disk if it changed since the last flush.
+/// Blocking; the periodic save runs it on the blocking pool.
+pub fn flush(app: &tauri::AppHandle) {
+    let store = app.state::<ServerStore>();
+    if !store.dirty.swap(false, Ordering::SeqCst) {
+        return;
+    }
+
+    // Serialize a copy so queries recording into the store aren't held up by the write
+    let snapshot = {
+        let mut records = store.records.lock().unwrap_or_else(|e| e.into_inner());
+        prune(&mut records, unix_now());
+        records.clone()
+    };
+    let json = serde_json::to_string(&snapshot);
+    let result = json
+        .map_err(|e| format!("Serialization failed: {}", e))
+        .and_then(|json| {
+            let path = get_store_path(app)?;
+            fs::write(&path, json).map_err(|e| format!("Failed to save server store: {}", e))
+        });
+    if let Err(e) = result {
+        log::warn!("[ServerStore] {}", e);
+        store.dirty.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Drop all in-memory records without writing them back (used by factory reset)
+pub fn clear(app: &tauri::AppHandle) {
+    let store = app.state::<ServerStore>();
+    store.records.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    store.dirty.store(false, Ordering::SeqCst);
+}
+
+/// Replace the in-memory records with the store file on disk (e.g. after a backup import)
+pub fn reload(app: &tauri::AppHandle) {
+    let loaded = load(app).records.into_inner().unwrap_or_else(|e| e.into_inner());
+    let store = app.state::<ServerStore>();
+    *store.records.lock().unwrap_or_else(|e| e.into_inner()) = loaded;
+    store.dirty.store(false, Ordering::SeqCst);
+}
+
+/// Run a closure against a server's record, creating it if needed
+pub fn with_record<T, F: FnOnce(&mut ServerRecord) -> T>(app: &tauri::AppHandle, key: &str, f: F) -> T {
+    let store = app.state::<ServerStore>();
+    let mut records = store.records.lock().unwrap_or_else(|e| e.into_inner());
+    let value = f(records.entry(key.to_string()).or_default());
+    store.dirty.store(true, Ordering::SeqCst);
+    value
+}
+
+/// Read a server's record without modifying the store
+pub fn get_record(app: &tauri::AppHandle, key: &str) -> Option<ServerRecord> {
+    let store = app.state::<ServerStore>();
+    let records = store.records.lock().unwrap_or_else(|e| e.into_inner());
+    records.get(key).cloned()
+}
+
+/// Record the outcome of an A2S query into the server's history
+pub fn record_query(app: &tauri::AppHandle, result: &crate::A2SQueryResult) {
+    let sample = QuerySample {
+        timestamp: unix_now(),
+        online: result.success,
+        players: result.players,
+        ping_ms: if result.success { Some(result.ping_ms) } else { None },
+    };
+    with_record(app, &server_key(&result.ip, &result.port), |record| {
+        if result.success && record.first_seen.is_none() {
+            record.first_seen = Some(sample.timestamp);
+        }
+        prune_samples(record, sample.timestamp.saturating_sub(SAMPLE_RETENTION_SECS));
+        record.samples.push_back(sample);
+        while record.samples.len() > MAX_SAMPLES_PER_SERVER {
+            record.samples.pop_front();
+        }
+    });
+}
+
+/// Get a server's uptime percentage over the last window_hours from recorded query samples.
+/// Returns None when there aren't enough samples in the window.
+#[tauri::command]
+pub async fn get_server_uptime(
+    app: tauri::AppHandle,
+    ip: String,
+    port: String,
+    window_hours: u64,
+) -> Result<Option<ServerUptime>, String> {
+    let since = unix_now().saturating_sub(window_hours.saturating_mul(3600));
+    let record = match get_record(&app, &server_key(&ip, &port)) {
+        Some(r) => r,
+        None => return Ok(None),
+    };
+
+    let in_window: Vec<&QuerySample> = record.samples.iter().filter(|s| s.timestamp >= since).collect();
+    if in_window.len() < MIN_UPTIME_SAMPLES {
+        return Ok(None);
+    }
+
+    let online = in_window.iter().filter(|s| s.online).count();
+    Ok(Some(ServerUptime {
+        uptime_percent: online as f64 * 100.0 / in_window.len() as f64,
+        sample_count: in_window.len(),
+    }))
+}
+
+/// Get a server's mean ping over the last window_minutes from recorded query samples.
+/// Returns None when there aren't enough successful samples so the UI can fall back to a live ping.
+#[tauri::command]
+pub async fn get_average_ping(
+    app: tauri::AppHandle,
+    ip: String,
+    port: String,
+    window_minutes: u64,
+) -> Result<Option<AveragePing>, String> {
+    let since = unix_now().saturating_sub(window_minutes.saturating_mul(60));
+    let record = match get_record(&app, &server_key(&ip, &port)) {
+        Some(r) => r,
+        None => return Ok(None),
+    };
+
+    let pings: Vec<u32> = record
+        .samples
+        .iter()
+        .filter(|s| s.timestamp >= since)
+        .filter_map(|s| s.ping_ms)
+        .collect();
+    if pings.len() < MIN_PING_SAMPLES {
+        return Ok(None);
+    }
+
+    let total: u64 = pings.iter().map(|p| *p as u64).sum();
+    Ok(Some(AveragePing {
+        average_ping_ms: total as f64 / pings.len() as f64,
+        sample_count: pings.len(),
+    }))
+}
+
+/// Get when a server was first successfully queried and how many days it has been known.
+/// Returns None for servers that have never answered a query.
+#[tauri::command]
+pub async fn get_server_age(app: tauri::AppHandle, ip: String, port: String) -> Result<Option<ServerAge>, String> {
+    let first_seen = match get_record(&app, &server_key(&ip, &port)).and_then(|r| r.first_seen) {
+        Some(t) => t,
+        None => return Ok(None),
+    };
+    Ok(Some(ServerAge {
+        first_seen,
+        days_known: unix_now().saturating_sub(first_seen) as f64 / 86400.0,
+    }))
+}
+
+/// Store per-server query overrides (timeout, query port, source IP) for ip:port
+#[tauri::command]
+pub async fn set_server_overrides(
+    app: tauri::AppHandle,
+    ip: String,
+    port: String,
+    overrides: ServerOverrides,
+) -> Result<(), String> {
+    if let Some(timeout) = overrides.timeout_ms {
+        if !OVERRIDE_TIMEOUT_RANGE_MS.contains(&timeout) {
+            return Err(format!(
+                "timeout_ms must be between {} and {}",
+                OVERRIDE_TIMEOUT_RANGE_MS.start(),
+                OVERRIDE_TIMEOUT_RANGE_MS.end()
+            ));
+        }
+    }
+    if overrides.query_port == Some(0) {
+        return Err("query_port must be non-zero".to_string());
+    }
+    // Make sure an override address belongs to a local interface (e.g. a VPN adapter that is up)
+    if let Some(addr) = crate::parse_source_ip(overrides.source_ip.as_deref())? {
+        std::net::UdpSocket::bind(std::net::SocketAddr::new(addr, 0))
+            .map_err(|e| format!("Failed to bind to source IP {}: {}", addr, e))?;
+    }
+
+    with_record(&app, &server_key(&ip, &port), |record| record.overrides = overrides);
+    tokio::task::spawn_blocking(move || flush(&app))
+        .await
+        .map_err(|e| format!("Save task failed: {}", e))?;
+    Ok(())
+}
+
+/// Get the per-server query overrides for ip:port (all unset if none were stored)
+#[tauri::command]
+pub async fn get_server_overrides(app: tauri::AppHandle, ip: String, port: String) -> Result<ServerOverrides, String> {
+    Ok(get_record(&app, &server_key(&ip, &port))
+        .map(|r| r.overrides)
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(timestamp: u64) -> QuerySample {
+        QuerySample { timestamp, online: true, players: 1, ping_ms: Some(20) }
+    }
+
+    #[test]
+    fn prune_drops_old_samples_and_idle_servers() {
+        let now = 100 * 24 * 3600;
+        let old = now - SAMPLE_RETENTION_SECS - 1;
+        let mut records = HashMap::new();
+        records.insert("active".to_string(), ServerRecord {
+            samples: VecDeque::from(vec![sample(old), sample(now - 60)]),
+            ..Default::default()
+        });
+        records.insert("idle".to_string(), ServerRecord {
+            samples: VecDeque::from(vec![sample(old)]),
+            ..Default::default()
+        });
+        records.insert("overridden".to_string(), ServerRecord {
+            samples: VecDeque::from(vec![sample(old)]),
+            overrides: ServerOverrides { query_port: Some(27016), ..Default::default() },
+            ..Default::default()
+        });
+
+        prune(&mut records, now);
+        assert_eq!(records["active"].samples.len(), 1);
+        assert!(!records.contains_key("idle"));
+        assert!(records["overridden"].samples.is_empty());
+    }
+}
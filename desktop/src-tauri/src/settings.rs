@@ -0,0 +1,183 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::Manager;
+
+/// Persistent application settings (stored as settings.json in the app data directory)
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct AppSettings {
+    /// Local IP address A2S queries are sent from (None = let the OS pick the route)
+    pub source_ip: Option<String>,
+    /// Fill ratios used to classify server population
+    pub population_thresholds: PopulationThresholds,
+    /// Account to sign in automatically on startup
+    pub last_active_steamid64: Option<String>,
+    /// Skip injecting the multi-tab bar into forum/browser windows
+    pub tab_bar_disabled: bool,
+    /// Back/forward entries kept per tab by the tab manager (None = DEFAULT_TAB_HISTORY_LIMIT)
+    pub tab_history_limit: Option<usize>,
+    /// Restore the forum window's tabs from the previous session
+    pub restore_forum_tabs: bool,
+    /// Ask the frontend to re-authenticate when the forum opens logged out and credentials are saved
+    pub forum_auto_reauth: bool,
+    /// Name of the predefined theme injected into forum pages (None = site default)
+    pub forum_theme: Option<String>,
+    /// Favorites autorefresh interval (None = disabled)
+    pub favorites_refresh_interval_secs: Option<u64>,
+    /// Only release the stored securecode after a fresh OS authentication prompt
+    /// (or the fallback passphrase in securecode_passphrase.json where there is no prompt)
+    pub require_os_auth_for_securecode: bool,
+    /// Cap on simultaneous A2S queries in batch queries (None = BATCH_QUERY_CONCURRENCY)
+    pub max_concurrency: Option<usize>,
+    /// Receive buffer of pooled batch query sockets in bytes (None = OS default)
+    pub udp_recv_buffer_bytes: Option<usize>,
+    /// Retry failed local A2S queries through the backend relay (for networks blocking UDP)
+    pub query_relay_enabled: bool,
+    /// Webview zoom factor per hostname (hosts not listed use 1.0)
+    pub zoom_by_host: std::collections::HashMap<String, f64>,
+    /// Power saver preference (None = automatic: on while running on battery)
+    pub power_saver: Option<bool>,
+    /// Desktop notifications for favorite server transitions
+    pub notifications: NotificationSettings,
+    /// Preferred color scheme of browser windows: "light" or "dark" (None = follow the OS)
+    pub color_scheme: Option<String>,
+}
+
+/// Fill ratios (players / max_players) separating the population states
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct PopulationThresholds {
+    /// Minimum fill ratio for a server to count as Active (below is Low)
+    pub active_ratio: f64,
+    /// Minimum fill ratio for a server to count as NearFull
+    pub near_full_ratio: f64,
+}
+
+impl Default for PopulationThresholds {
+    fn default() -> Self {
+        PopulationThresholds {
+            active_ratio: 0.3,
+            near_full_ratio: 0.8,
+        }
+    }
+}
+
+/// Which favorite server transitions (seen by the favorites autorefresh) raise a desktop
+/// notification, and how often one server may notify
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct NotificationSettings {
+    /// Master switch, off until the user opts in
+    pub enabled: bool,
+    /// A server came online or went offline
+    pub online: bool,
+    /// A server changed map
+    pub map_change: bool,
+    /// Human players reached player_threshold
+    pub threshold: bool,
+    pub player_threshold: i32,
+    /// Transitions within this many seconds of the server's last notification are suppressed
+    pub cooldown_secs: u64,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        NotificationSettings {
+            enabled: false,
+            online: true,
+            map_change: true,
+            threshold: true,
+            player_threshold: 10,
+            cooldown_secs: 5 * 60,
+        }
+    }
+}
+
+/// Managed state holding the settings loaded at startup
+pub struct SettingsState(pub Mutex<AppSettings>);
+
+/// Get settings file path
+fn get_settings_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+
+    // Ensure directory exists
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+
+    Ok(app_data_dir.join("settings.json"))
+}
+
+/// Load settings from disk, falling back to defaults if missing or unreadable
+pub fn load_settings(app: &tauri::AppHandle) -> AppSettings {
+    let path = match get_settings_path(app) {
+        Ok(p) => p,
+        Err(e) => {
+            log::warn!("[Settings] {}", e);
+            return AppSettings::default();
+        }
+    };
+
+    if !path.exists() {
+        return AppSettings::default();
+    }
+
+    match fs::read_to_string(&path) {
+        Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            log::warn!("[Settings] Failed to parse settings, using defaults: {}", e);
+            AppSettings::default()
+        }),
+        Err(e) => {
+            log::warn!("[Settings] Failed to read settings, using defaults: {}", e);
+            AppSettings::default()
+        }
+    }
+}
+
+/// Write settings to disk
+fn save_settings(app: &tauri::AppHandle, settings: &AppSettings) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Serialization failed: {}", e))?;
+    let path = get_settings_path(app)?;
+    fs::write(&path, json)
+        .map_err(|e| format!("Failed to save settings: {}", e))
+}
+
+/// Get a snapshot of the current settings
+pub fn current(app: &tauri::AppHandle) -> AppSettings {
+    let state = app.state::<SettingsState>();
+    let settings = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    settings.clone()
+}
+
+/// Apply a change to the settings and persist it
+pub fn update<F: FnOnce(&mut AppSettings)>(app: &tauri::AppHandle, f: F) -> Result<AppSettings, String> {
+    let state = app.state::<SettingsState>();
+    let mut settings = state.0.lock().map_err(|e| e.to_string())?;
+    f(&mut settings);
+    save_settings(app, &settings)?;
+    Ok(settings.clone())
+}
+
+/// Reset the in-memory settings to defaults (the file is removed by the caller)
+pub fn reset(app: &tauri::AppHandle) {
+    let state = app.state::<SettingsState>();
+    let mut settings = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    *settings = AppSettings::default();
+}
+
+/// Re-read the settings file into memory (after it was replaced on disk, e.g. by a backup import)
+pub fn reload(app: &tauri::AppHandle) {
+    let loaded = load_settings(app);
+    let state = app.state::<SettingsState>();
+    *state.0.lock().unwrap_or_else(|e| e.into_inner()) = loaded;
+}
+
+/// Get the current application settings
+#[tauri::command]
+pub async fn get_settings(app: tauri::AppHandle) -> Result<AppSettings, String> {
+    Ok(current(&app))
+}
@@ -0,0 +1,162 @@
+use std::process::Command;
+
+/// Error code returned when Steam isn't running, so the UI can prompt the user to start it
+pub const STEAM_NOT_RUNNING: &str = "SteamNotRunning";
+
+/// Error code returned when a server address can't be put into a steam://connect URL
+pub const INVALID_SERVER_ADDRESS: &str = "InvalidServerAddress";
+
+/// Build a command that doesn't flash a console window on Windows
+pub fn hidden_command(program: &str) -> Command {
+    #[allow(unused_mut)]
+    let mut cmd = Command::new(program);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // CREATE_NO_WINDOW
+        cmd.creation_flags(0x0800_0000);
+    }
+    cmd
+}
+
+/// Read Steam's install path from the registry and verify it exists
+#[cfg(windows)]
+fn steam_install_path() -> Option<std::path::PathBuf> {
+    let output = hidden_command("reg")
+        .args(["query", r"HKCU\Software\Valve\Steam", "/v", "SteamPath"])
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Output line format: "    SteamPath    REG_SZ    c:/program files (x86)/steam"
+    let path = stdout
+        .lines()
+        .find(|line| line.trim_start().starts_with("SteamPath"))?
+        .split("REG_SZ")
+        .nth(1)?
+        .trim()
+        .to_string();
+    let path = std::path::PathBuf::from(path);
+    if path.exists() { Some(path) } else { None }
+}
+
+/// Check whether the Steam client process is running
+fn steam_process_running() -> bool {
+    #[cfg(windows)]
+    {
+        hidden_command("tasklist")
+            .args(["/FI", "IMAGENAME eq steam.exe", "/NH"])
+            .output()
+            .map(|o| String::from_utf8_lossy(&o.stdout).to_lowercase().contains("steam.exe"))
+            .unwrap_or(false)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        hidden_command("pgrep")
+            .args(["-x", "steam_osx"])
+            .status()
+            .map(|s| s.success())
+            .unwrap_or(false)
+    }
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        hidden_command("pgrep")
+            .args(["-x", "steam"])
+            .status()
+            .map(|s| s.success())
+            .unwrap_or(false)
+    }
+}
+
+/// Probe whether Steam is installed (Windows) and running
+pub fn steam_running() -> bool {
+    #[cfg(windows)]
+    {
+        if steam_install_path().is_none() {
+            log::warn!("[Steam] Steam install path not found in registry");
+            return false;
+        }
+    }
+    steam_process_running()
+}
+
+/// Percent-encode everything but unreserved characters, so a password can't add path
+/// segments or be altered by the handler ("/", spaces, "+", "%")
+fn encode_segment(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Build the steam://connect URL for a server (password is optional).
+/// The address is validated first, so only a well-formed host:port reaches Steam.
+pub fn connect_url(ip: &str, port: &str, password: Option<&str>) -> Result<String, String> {
+    let (host, port) = crate::parse_server_address(&format!("{}:{}", ip.trim(), port.trim()))
+        .ok_or_else(|| format!("{}: 无效的服务器地址: {}:{}", INVALID_SERVER_ADDRESS, ip, port))?;
+    let address = if host.contains(':') { format!("[{}]:{}", host, port) } else { format!("{}:{}", host, port) };
+    Ok(match password.filter(|p| !p.is_empty()) {
+        Some(pw) => format!("steam://connect/{}/{}", address, encode_segment(pw)),
+        None => format!("steam://connect/{}", address),
+    })
+}
+
+/// Open a steam:// URL with the system handler after checking Steam is running
+pub fn open_connect_url(app: &tauri::AppHandle, url: &str) -> Result<(), String> {
+    if !steam_running() {
+        return Err(format!("{}: Steam 未运行，请先启动 Steam", STEAM_NOT_RUNNING));
+    }
+
+    use tauri_plugin_shell::ShellExt;
+    #[allow(deprecated)]
+    app.shell()
+        .open(url, None)
+        .map_err(|e| format!("Failed to open Steam URL: {}", e))
+}
+
+/// Check whether the Steam client is running
+#[tauri::command]
+pub async fn is_steam_running() -> Result<bool, String> {
+    tokio::task::spawn_blocking(steam_running)
+        .await
+        .map_err(|e| format!("Steam check failed: {}", e))
+}
+
+/// Join a server through steam://connect, failing with SteamNotRunning if Steam isn't running
+#[tauri::command]
+pub async fn launch_game(
+    app: tauri::AppHandle,
+    ip: String,
+    port: String,
+    password: Option<String>,
+) -> Result<bool, String> {
+    let url = connect_url(&ip, &port, password.as_deref())?;
+    tokio::task::spawn_blocking(move || open_connect_url(&app, &url))
+        .await
+        .map_err(|e| format!("Launch task failed: {}", e))??;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connect_url_encodes_the_password() {
+        assert_eq!(connect_url("1.2.3.4", "27015", None).unwrap(), "steam://connect/1.2.3.4:27015");
+        assert_eq!(
+            connect_url("1.2.3.4", "27015", Some("a/b c+d%")).unwrap(),
+            "steam://connect/1.2.3.4:27015/a%2Fb%20c%2Bd%25"
+        );
+        assert_eq!(connect_url("2001:db8::1", "27015", None).unwrap(), "steam://connect/[2001:db8::1]:27015");
+    }
+
+    #[test]
+    fn connect_url_rejects_invalid_addresses() {
+        assert!(connect_url("1.2.3.4/+exec evil", "27015", None).is_err());
+        assert!(connect_url("1.2.3.4", "0", None).is_err());
+        assert!(connect_url("1.2.3.4", "27015/x", None).is_err());
+    }
+}
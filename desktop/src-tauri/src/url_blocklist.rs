@@ -0,0 +1,188 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+use url::Url;
+
+/// A blocked URL pattern of the form "host[/path][?query]" (scheme ignored, case-insensitive).
+/// The host matches itself and its subdomains, the path matches whole segments and the query
+/// whole parameters, so "evil.example" blocks sub.evil.example but not evil.example.com, and
+/// "bbs.upkk.com/home.php?mod=spacecp" blocks that page (with any further parameters).
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct BlockedUrl {
+    pub pattern: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Payload of the navigation-blocked event
+#[derive(serde::Serialize, Clone, Debug)]
+struct NavigationBlocked {
+    url: String,
+    reason: String,
+}
+
+/// Managed state holding the blocklist, persisted to url_blocklist.json.
+/// The navigation allowlist stays the primary control; this is a safety net for
+/// malicious pages on allowlisted domains (e.g. phishing links posted on the forum).
+#[derive(Default)]
+pub struct UrlBlocklist(pub Mutex<Vec<BlockedUrl>>);
+
+/// Get blocklist file path
+fn get_blocklist_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+
+    // Ensure directory exists
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+
+    Ok(app_data_dir.join("url_blocklist.json"))
+}
+
+/// Load the blocklist from disk (empty if missing or unreadable)
+pub fn load(app: &tauri::AppHandle) -> UrlBlocklist {
+    let entries = get_blocklist_path(app)
+        .ok()
+        .filter(|path| path.exists())
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| match serde_json::from_str(&json) {
+            Ok(entries) => Some(entries),
+            Err(e) => {
+                log::warn!("[Blocklist] Failed to parse blocklist: {}", e);
+                None
+            }
+        })
+        .unwrap_or_default();
+    UrlBlocklist(Mutex::new(entries))
+}
+
+/// Re-read the blocklist file into memory (e.g. after a backup import)
+pub fn reload(app: &tauri::AppHandle) {
+    let loaded = load(app).0.into_inner().unwrap_or_else(|e| e.into_inner());
+    *app.state::<UrlBlocklist>().0.lock().unwrap_or_else(|e| e.into_inner()) = loaded;
+}
+
+/// Whether a normalized pattern matches the URL: same host or a subdomain of it, then the
+/// pattern path as a prefix ending on a segment boundary, then the pattern query as a prefix
+/// ending on a parameter boundary (a pattern with a query only matches that exact path)
+fn pattern_matches(pattern: &str, url: &Url) -> bool {
+    let host = match url.host_str() {
+        Some(host) => host.to_ascii_lowercase(),
+        None => return false,
+    };
+    let split = pattern.find(['/', '?']).unwrap_or(pattern.len());
+    let (host_pattern, rest) = pattern.split_at(split);
+    if host_pattern.is_empty()
+        || !(host == host_pattern
+            || host.strip_suffix(host_pattern).is_some_and(|prefix| prefix.ends_with('.')))
+    {
+        return false;
+    }
+
+    let (path_pattern, query_pattern) = match rest.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (rest, None),
+    };
+    let path = url.path().to_ascii_lowercase();
+    let path_pattern = if path_pattern.is_empty() { "/" } else { path_pattern };
+    match query_pattern {
+        None => {
+            path == path_pattern
+                || path_pattern.ends_with('/') && path.starts_with(path_pattern)
+                || path.strip_prefix(path_pattern).is_some_and(|tail| tail.starts_with('/'))
+        }
+        Some(query_pattern) => {
+            let query = url.query().unwrap_or("").to_ascii_lowercase();
+            path == path_pattern
+                && (query == query_pattern
+                    || query.strip_prefix(query_pattern).is_some_and(|tail| tail.starts_with('&')))
+        }
+    }
+}
+
+/// Normalize a pattern: drop any scheme, lowercase, trim whitespace
+fn normalize_pattern(pattern: &str) -> String {
+    let trimmed = pattern.trim();
+    let without_scheme = trimmed.split_once("://").map(|(_, rest)| rest).unwrap_or(trimmed);
+    without_scheme.to_ascii_lowercase()
+}
+
+/// Reason a URL is blocked, if it matches a blocklist entry
+fn blocked_reason(app: &tauri::AppHandle, url: &Url) -> Option<String> {
+    let blocklist = app.state::<UrlBlocklist>();
+    let entries = blocklist.0.lock().unwrap_or_else(|e| e.into_inner());
+    entries
+        .iter()
+        .find(|entry| pattern_matches(&entry.pattern, url))
+        .map(|entry| entry.reason.clone().unwrap_or_else(|| "该网址已被列入黑名单".to_string()))
+}
+
+/// Check a navigation against the blocklist from on_navigation.
+/// Returns true (and emits navigation-blocked) when the navigation must be cancelled.
+pub fn check_navigation(app: &tauri::AppHandle, url: &Url) -> bool {
+    match blocked_reason(app, url) {
+        Some(reason) => {
+            log::warn!("[Blocklist] Blocked navigation to {}: {}", url, reason);
+            let _ = app.emit("navigation-blocked", NavigationBlocked {
+                url: url.to_string(),
+                reason,
+            });
+            true
+        }
+        None => false,
+    }
+}
+
+/// Replace the URL blocklist. Empty patterns are dropped; returns how many entries were stored.
+#[tauri::command]
+pub async fn update_url_blocklist(app: tauri::AppHandle, entries: Vec<BlockedUrl>) -> Result<usize, String> {
+    let entries: Vec<BlockedUrl> = entries
+        .into_iter()
+        .map(|entry| BlockedUrl {
+            pattern: normalize_pattern(&entry.pattern),
+            reason: entry.reason.filter(|r| !r.trim().is_empty()),
+        })
+        .filter(|entry| !entry.pattern.is_empty())
+        .collect();
+
+    let json = serde_json::to_string_pretty(&entries)
+        .map_err(|e| format!("Serialization failed: {}", e))?;
+    let path = get_blocklist_path(&app)?;
+    fs::write(&path, json).map_err(|e| format!("Failed to save blocklist: {}", e))?;
+
+    let count = entries.len();
+    *app.state::<UrlBlocklist>().0.lock().map_err(|e| e.to_string())? = entries;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, url: &str) -> bool {
+        pattern_matches(&normalize_pattern(pattern), &Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn host_patterns_match_on_labels() {
+        assert!(matches("evil.example", "https://evil.example/"));
+        assert!(matches("evil.example", "https://sub.evil.example/page"));
+        assert!(!matches("evil.example", "https://evil.example.com/"));
+        assert!(!matches("evil.example", "https://evil.examplefoo.com/"));
+        assert!(!matches("evil.example", "https://notevil.example/"));
+    }
+
+    #[test]
+    fn path_and_query_patterns_respect_boundaries() {
+        assert!(matches("bbs.upkk.com/forum", "https://bbs.upkk.com/forum"));
+        assert!(matches("bbs.upkk.com/forum", "https://bbs.upkk.com/forum/thread-1.html"));
+        assert!(!matches("bbs.upkk.com/forum", "https://bbs.upkk.com/forum.php"));
+        assert!(matches("https://bbs.upkk.com/home.php?mod=spacecp", "https://bbs.upkk.com/home.php?mod=spacecp"));
+        assert!(matches("bbs.upkk.com/home.php?mod=spacecp", "https://bbs.upkk.com/home.php?mod=spacecp&ac=avatar"));
+        assert!(!matches("bbs.upkk.com/home.php?mod=spacecp", "https://bbs.upkk.com/home.php?mod=spacecpx"));
+        assert!(!matches("bbs.upkk.com/home.php?mod=spacecp", "https://bbs.upkk.com/home.php"));
+    }
+}